@@ -0,0 +1,146 @@
+//迷路生成のコマンドラインツール（ウィンドウを開かずに迷路をテキスト形式で出力する）
+//例：chip14-gen --seed 42 --size 31x21 --algorithm prim --output maze.maze
+
+//external crates
+use bevy::prelude::IVec2;
+use rand::prelude::*;
+
+//standard library
+use std::process::ExitCode;
+
+//迷路の中核はゲーム本体と同じライブラリを使う
+use chip14::maze::*;
+use chip14::shared::*;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//使い方
+const USAGE: &str = "\
+usage: chip14-gen [--seed N] [--size WxH] [--algorithm NAME] [--braid F] [--mask FILE]
+                  [--prefabs FILE] [--floors N] [--output FILE] [--stats]
+  --seed N         maze seed (random if omitted)
+  --size WxH       map size including the outer wall, or N for NxN
+  --algorithm NAME digger, prim, kruskal, wilson, eller, hunt-and-kill,
+                   sidewinder, recursive-division, dungeon or cave
+  --braid F        ratio of dead ends turned into loops, 0.0 to 1.0 (default 0.0)
+  --mask FILE      dig only inside the shape in FILE (text, or .png), digger only
+  --prefabs FILE   embed the fixed areas in FILE (maze text format), digger only
+  --floors N       number of floors linked by stairs, 1 to 9 (default 1)
+  --output FILE    write the maze to FILE instead of stdout
+  --stats          print the maze analysis to stderr";
+
+//受け付けるオプション（名前と、値を取るか）
+const OPTIONS: &[ ( &str, bool ) ] =
+&[  ( "seed"     , true  ),
+    ( "size"     , true  ),
+    ( "algorithm", true  ),
+    ( "braid"    , true  ),
+    ( "mask"     , true  ),
+    ( "prefabs"  , true  ),
+    ( "floors"   , true  ),
+    ( "output"   , true  ),
+    ( "stats"    , false ),
+];
+
+////////////////////////////////////////////////////////////////////////////////
+
+//メイン関数
+fn main() -> ExitCode
+{   let args: Vec<String> = std::env::args().skip( 1 ).collect();
+    if args.iter().any( | arg | arg == "--help" || arg == "-h" )
+    {   println!( "{USAGE}" );
+        return ExitCode::SUCCESS
+    }
+
+    match check_args( &args ).and_then( | _ | run() )
+    {   Ok (()) => ExitCode::SUCCESS,
+        Err ( err ) =>
+        {   eprintln!( "chip14-gen: {err}\n{USAGE}" );
+            ExitCode::FAILURE
+        }
+    }
+}
+
+//知らないオプション・値の抜け・余分な引数をエラーにする
+//（値はmisc::setting()で読むので、ここでは形だけを確かめる）
+fn check_args( args: &[ String ] ) -> Result<(), String>
+{   let mut args = args.iter();
+    while let Some ( arg ) = args.next()
+    {   let Some ( option ) = arg.strip_prefix( "--" )
+            else { return Err ( format!( "unexpected argument \"{arg}\"" ) ) };
+        let ( name, value ) = match option.split_once( '=' )
+        {   Some ( ( name, value ) ) => ( name, Some ( value ) ),
+            None => ( option, None ),
+        };
+        let Some ( &( _, has_value ) ) = OPTIONS.iter().find( | ( option, _ ) | *option == name )
+            else { return Err ( format!( "unknown option \"--{name}\"" ) ) };
+
+        match ( has_value, value )
+        {   ( true, None ) if args.next().is_none_or( | value | value.starts_with( "--" ) ) =>
+                return Err ( format!( "missing value for \"--{name}\"" ) ),
+            ( false, Some ( _ ) ) =>
+                return Err ( format!( "\"--{name}\" takes no value" ) ),
+            _ => (),
+        }
+    }
+
+    Ok (())
+}
+
+//迷路を作って出力する
+fn run() -> Result<(), String>
+{   //オプションはゲーム本体と同じくmisc::setting()で読む（設定ファイルも有効）
+    let seed = match misc::setting( "seed" )
+    {   Some ( seed ) => seed.parse().map_err( | _ | format!( "invalid seed \"{seed}\"" ) )?,
+        None => rand::thread_rng().gen(),
+    };
+    let algorithm = match misc::setting( "algorithm" )
+    {   Some ( name ) => name.parse()?,
+        None => Algorithm::default(),
+    };
+    let size = match misc::setting( "size" )
+    {   Some ( size ) => parse_size( &size ).ok_or( format!( "invalid size \"{size}\"" ) )?,
+        None => IVec2::new( MAP_GRIDS_WIDTH, MAP_GRIDS_HEIGHT ),
+    };
+    let mut map = Map::new( size, algorithm );
+    if let Some ( braid ) = misc::setting( "braid" )
+    {   map.braid = parse_braid( &braid ).ok_or( format!( "invalid braid factor \"{braid}\"" ) )?;
+    }
+    if let Some ( path ) = misc::setting( "mask" )
+    {   map.mask = Some ( MazeMask::load( &path )? );
+    }
+    if let Some ( path ) = misc::setting( "prefabs" )
+    {   map.prefabs = load_prefabs( &path )?;
+    }
+    if let Some ( floors ) = misc::setting( "floors" )
+    {   map.floors = parse_floors( &floors ).ok_or( format!( "invalid floors \"{floors}\"" ) )?;
+    }
+
+    //迷路を作る（ゲーム本体と同じシードなら同じ迷路になる）
+    map.check_algorithm()?;
+    map.reseed( seed );
+    map.generate();
+
+    //シードとアルゴリズムをコメントとして残す（読込み時は読み飛ばされる）
+    let text = format!( "//seed {seed} algorithm {:?} braid {}\n{}", map.algorithm, map.braid, map.to_ascii() );
+    match misc::setting( "output" )
+    {   Some ( path ) => std::fs::write( &path, text ).map_err( | err | format!( "{path}: {err}" ) )?,
+        None => print!( "{text}" ),
+    }
+
+    if misc::setting_flag( "stats" )
+    {   //複数階なら階ごとに出力する
+        let floor_stats = map.floor_stats();
+        let is_multi = floor_stats.len() > 1;
+        for ( floor, stats ) in floor_stats.into_iter().enumerate()
+        {   if is_multi { eprint!( "floor {}: ", floor + 1 ) }
+            eprintln!( "{stats}" );
+        }
+    }
+
+    Ok (())
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
\ No newline at end of file
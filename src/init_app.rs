@@ -23,6 +23,16 @@ impl Plugin for Schedule
         //footerにFPSを表示する
         .add_plugins( FrameTimeDiagnosticsPlugin ) //FPSプラグイン
         .add_systems( Update, update_fps )         //FPS表示更新
+
+        //footerに迷路のシードとレベル・スコアを、ウィンドウのタイトルにシードを表示する
+        .add_systems
+        (   Update,
+            (   update_seed,     //シード表示更新
+                update_progress, //レベルとスコアの表示更新
+                update_window_title
+                    .run_if( resource_exists_and_changed::<MazeSeed>() ),
+            )
+        )
         ;
     }
 }
@@ -41,13 +51,30 @@ type MessageSect<'a> =
 #[derive( Component )]
 struct FooterUiFps;
 
+//フッター(シード表示)のComponent
+#[derive( Component )]
+struct FooterUiSeed;
+
+//フッター(レベルとスコア表示)のComponent
+#[derive( Component )]
+struct FooterUiProgress;
+
 //フッターの設定
 const NA3_2: &str = "###.##";
+const NA_SEED: &str = "#";
+const NA_LEVEL: &str = "#";
+const NA_SCORE: &str = "#";
 
 counted_array!
 (   const TEXT_FOOTER_LEFT: [ MessageSect; _ ] =
-    [   ( " FPS ", ASSETS_FONT_ORBITRON_BLACK      , PIXELS_PER_GRID * 0.6, Color::TEAL   ),
-        ( NA3_2  , ASSETS_FONT_PRESSSTART2P_REGULAR, PIXELS_PER_GRID * 0.4, Color::SILVER ),
+    [   ( " FPS "  , ASSETS_FONT_ORBITRON_BLACK      , PIXELS_PER_GRID * 0.6, Color::TEAL   ),
+        ( NA3_2    , ASSETS_FONT_PRESSSTART2P_REGULAR, PIXELS_PER_GRID * 0.4, Color::SILVER ),
+        ( " SEED " , ASSETS_FONT_ORBITRON_BLACK      , PIXELS_PER_GRID * 0.6, Color::TEAL   ),
+        ( NA_SEED  , ASSETS_FONT_PRESSSTART2P_REGULAR, PIXELS_PER_GRID * 0.4, Color::SILVER ),
+        ( " LV "   , ASSETS_FONT_ORBITRON_BLACK      , PIXELS_PER_GRID * 0.6, Color::TEAL   ),
+        ( NA_LEVEL , ASSETS_FONT_PRESSSTART2P_REGULAR, PIXELS_PER_GRID * 0.4, Color::SILVER ),
+        ( " SCORE ", ASSETS_FONT_ORBITRON_BLACK      , PIXELS_PER_GRID * 0.6, Color::TEAL   ),
+        ( NA_SCORE , ASSETS_FONT_PRESSSTART2P_REGULAR, PIXELS_PER_GRID * 0.4, Color::SILVER ),
     ]
 );
 
@@ -129,9 +156,9 @@ fn spawn_footer
     //隠しフレームの中に子要素を作成する
     cmds.spawn( hidden_frame ).with_children
     (   | cmds |
-        {   cmds.spawn( ( footer_left, FooterUiFps ) );
-            cmds.spawn(   footer_center              );
-            cmds.spawn(   footer_right               );
+        {   cmds.spawn( ( footer_left, FooterUiFps, FooterUiSeed, FooterUiProgress ) );
+            cmds.spawn(   footer_center                                             );
+            cmds.spawn(   footer_right                                              );
         }
     );
 
@@ -199,6 +226,40 @@ fn update_fps
     text.sections[ 1 ].value = fps_avr;
 }
 
+//フッターを更新する(シード)
+fn update_seed
+(   mut q_text: Query<&mut Text, With<FooterUiSeed>>,
+    seed: Res<MazeSeed>,
+)
+{   let Ok( mut text ) = q_text.get_single_mut() else { return };
+
+    let seed = seed.0.to_string();
+    if text.sections[ 3 ].value != seed { text.sections[ 3 ].value = seed }
+}
+
+//フッターを更新する(レベルとスコア)
+fn update_progress
+(   mut q_text: Query<&mut Text, With<FooterUiProgress>>,
+    progress: Res<Progress>,
+)
+{   let Ok( mut text ) = q_text.get_single_mut() else { return };
+
+    let level = progress.level.to_string();
+    let score = progress.score.to_string();
+    if text.sections[ 5 ].value != level { text.sections[ 5 ].value = level }
+    if text.sections[ 7 ].value != score { text.sections[ 7 ].value = score }
+}
+
+//ウィンドウのタイトルを更新する(シード)
+fn update_window_title
+(   mut q_window: Query<&mut Window>,
+    seed: Res<MazeSeed>,
+)
+{   let Ok( mut window ) = q_window.get_single_mut() else { return };
+
+    window.title = format!( "{APP_TITLE} v{APP_VER} seed:{}", seed.0 );
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 //End of code.
\ No newline at end of file
@@ -0,0 +1,19 @@
+//迷路の中核のライブラリ（迷路データ・生成・分析・テキスト形式）
+//ゲーム本体(main.rs)と迷路生成ツール(bin/chip14-gen.rs)の両方から使う。描画やゲームのシステムは含まない
+
+//external crates
+use bevy::prelude::*;
+use rand::prelude::*;
+
+//standard library
+use std::ops::{ Range, Add, AddAssign };
+use std::f32::consts::PI;
+use std::collections::{ HashSet, VecDeque };
+
+//internal submodules
+pub mod shared;
+use shared::*;
+
+pub mod maze;
+
+//End of code.
\ No newline at end of file
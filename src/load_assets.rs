@@ -7,6 +7,10 @@ pub struct Schedule;
 impl Plugin for Schedule
 {   fn build( &self, app: &mut App )
     {   app
+        //迷路ファイル(.maze)をAssetとしてロードできるようにする
+        .add_asset::<play_game::MazeLevel>()
+        .init_asset_loader::<play_game::MazeLevelLoader>()
+
         //前処理
         .add_systems
         (   OnEnter ( MyState::LoadAssets ),
@@ -99,6 +103,13 @@ fn start_loading
     PRELOAD_ASSETS.iter()
     .for_each( | fname | handles.push( asset_svr.load_untyped( *fname ) ) );
 
+    //迷路ファイルの指定があればロードを開始（例：--maze levels/sample.maze）
+    if let Some ( fname ) = misc::setting( "maze" )
+    {   let handle: Handle<play_game::MazeLevel> = asset_svr.load( fname );
+        handles.push( handle.clone_untyped() );
+        cmds.insert_resource( play_game::LoadedMazeLevel ( handle ) );
+    }
+
     //解放しないようリソースに登録する
     cmds.insert_resource( LoadedAssets { handles } );
 }
@@ -4,8 +4,12 @@ use bevy::
     log::LogPlugin,
     core_pipeline::clear_color::ClearColorConfig,
     window::WindowMode,
-    asset::LoadState,
+    asset::{ LoadState, AssetLoader, LoadContext, LoadedAsset },
+    reflect::{ TypePath, TypeUuid },
+    utils::BoxedFuture,
     render::camera,
+    render::mesh::Indices,
+    render::render_resource::PrimitiveTopology,
     diagnostic::DiagnosticsStore,
     diagnostic::FrameTimeDiagnosticsPlugin,
     input::mouse,
@@ -15,8 +19,9 @@ use counted_array::counted_array;
 use rand::prelude::*;
 
 //standard library
-use std::ops::{ Range, Add, AddAssign };
+use std::ops::Range;
 use std::f32::consts::{ PI, TAU, FRAC_PI_2 };
+use std::collections::HashSet;
 
 //internal submodules
 mod public;
@@ -0,0 +1,454 @@
+use super::*;
+
+//internal submodules
+mod cell;
+pub use cell::*;
+
+mod generator;
+pub use generator::*;
+
+mod ascii;
+pub use ascii::*;
+
+mod path;
+pub use path::*;
+
+mod stats;
+pub use stats::*;
+
+mod fog;
+
+mod braid;
+pub use braid::*;
+
+mod mask;
+pub use mask::*;
+
+mod prefab;
+pub use prefab::*;
+
+mod floor;
+pub use floor::*;
+
+#[cfg( test )]
+mod tests;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//MapのResource
+#[derive( Resource )]
+pub struct Map
+{   rng: rand::prelude::StdRng, //専用乱数発生器
+    matrices: Vec<Vec<Vec<Cell>>>, //map（階ごと。[0]が一階）
+    floor : usize,              //今の階（地形やフラグのメソッドはこの階を扱う）
+    width : i32,                //マップの横幅(外壁含む)
+    height: i32,                //マップの縦幅(外壁含む)
+    pub start: IVec2,           //スタート位置（一階）
+    pub goal: Option<IVec2>,    //ゴール位置
+    pub goal_floor: usize,      //ゴールのある階
+    pub goal_hint: Option<IVec2>, //指定のゴール位置(たどり着けなければ使わない)
+    pub floors: usize,          //迷路を作る階数
+    pub algorithm: Algorithm,   //迷路生成アルゴリズム
+    pub braid: f32,             //行き止りを壊してループにする割合(0.0～1.0)
+    pub mask: Option<MazeMask>, //迷路を作る範囲の形(Noneなら外壁の内側全部)
+    pub prefabs: Vec<Prefab>,   //迷路に埋め込む固定の区画
+    pub build_log: Option<Vec<( usize, BuildStep )>>, //迷路を作る手順の記録(Noneなら記録しない)
+}
+
+//Map::default()の定義（コマンドライン引数と設定ファイルを読む）
+impl Default for Map
+{   fn default() -> Self
+    {   //迷路生成アルゴリズムはコマンドライン引数か設定ファイルで選べる（例：--algorithm prim）
+        let algorithm = misc::setting( "algorithm" ).map_or
+        (   Algorithm::default(),
+            | name | name.parse().unwrap_or_else( | err | { warn!( "{err}" ); default() } )
+        );
+
+        //マップの縦横幅もコマンドライン引数で選べる（例：--size 31x21）
+        let default_size = IVec2::new( MAP_GRIDS_WIDTH, MAP_GRIDS_HEIGHT );
+        let size = misc::setting( "size" ).map_or
+        (   default_size,
+            | size | parse_size( &size ).unwrap_or_else
+            (   || { warn!( "Invalid map size \"{size}\"" ); default_size }
+            )
+        );
+
+        Self
+        {   goal_hint: goal_setting(),
+            floors : floors_setting(),
+            braid  : braid_setting(),
+            mask   : mask_setting(),
+            prefabs: prefabs_setting(),
+            ..Self::new( size, algorithm )
+        }
+    }
+}
+
+impl Map
+{   //設定を読まずにMapを作る（一階だけで、ブレイド・マスク・プレハブ・ゴールの指定なし）
+    pub fn new( size: IVec2, algorithm: Algorithm ) -> Self
+    {   let mut map = Self
+        {   rng  : StdRng::seed_from_u64( 0 ), //make_new_data()でMazeSeedから再設定される
+            matrices: Vec::new(),
+            floor : 0,
+            width : 0,
+            height: 0,
+            start: IVec2::default(),
+            goal : None,
+            goal_floor: 0,
+            goal_hint : None,
+            floors: 1,
+            algorithm,
+            braid: 0.0,
+            mask : None,
+            prefabs: Vec::new(),
+            build_log: None,
+        };
+        map.resize( size );
+        map
+    }
+}
+
+//"WxH"形式（正方形なら"N"だけでもよい）の文字列からマップの縦横幅を得る
+pub fn parse_size( s: &str ) -> Option<IVec2>
+{   let ( w, h ) = s.split_once( [ 'x', 'X' ] ).unwrap_or( ( s, s ) );
+    Some ( IVec2::new( w.trim().parse().ok()?, h.trim().parse().ok()? ) )
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//Mapのメソッド
+impl Map
+{   //ユーティリティ
+    fn matrix_mut( &mut self, IVec2 { x, y }: IVec2 ) -> &mut Cell
+    {   &mut self.matrices[ self.floor ][ x as usize ][ y as usize ]
+    }
+    fn matrix( &self, IVec2 { x, y }: IVec2 ) -> &Cell
+    {   &self.matrices[ self.floor ][ x as usize ][ y as usize ]
+    }
+
+    //今の階の全体を埋める
+    fn fill_walls( &mut self )
+    {   self.matrices[ self.floor ].iter_mut().for_each
+        (   |column| column.fill( Cell::new( Terrain::Wall ) )
+        );
+    }
+
+    //指定の位置の地形を書き換える（フラグはクリアされる。地形が変われば手順を記録する）
+    fn set_space( &mut self, cell: IVec2 )
+    {   if ! self.is_inside( cell ) { return }
+        if ! self.is_space( cell ) { self.log_step( BuildStep::Dig ( cell ) ) }
+        *self.matrix_mut( cell ) = Cell::new( Terrain::Space );
+    }
+
+    fn set_wall( &mut self, cell: IVec2 )
+    {   if ! self.is_inside( cell ) { return }
+        if ! self.is_wall( cell ) { self.log_step( BuildStep::Fill ( cell ) ) }
+        *self.matrix_mut( cell ) = Cell::new( Terrain::Wall );
+    }
+
+    //迷路を作る手順を記録する（記録中なら）
+    fn log_step( &mut self, step: BuildStep )
+    {   let floor = self.floor;
+        if let Some ( log ) = &mut self.build_log { log.push( ( floor, step ) ) }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//Mapのpubメソッド
+impl Map
+{   //乱数発生器をシードで初期化する（同じシードなら同じ迷路になる）
+    pub fn reseed( &mut self, seed: u64 )
+    {   self.rng = StdRng::seed_from_u64( seed );
+    }
+
+    //専用乱数発生器（迷路に付随する乱数は全てここから得る）
+    pub fn rng( &mut self ) -> &mut StdRng
+    {   &mut self.rng
+    }
+
+    //マップの縦横幅を変更する（地形は未定義の一階だけに戻るので、この後に迷路を作ること）
+    pub fn resize( &mut self, size: IVec2 )
+    {   let size = size.clamp( IVec2::splat( MAP_GRIDS_MIN ), IVec2::splat( MAP_GRIDS_MAX ) );
+        self.width  = size.x;
+        self.height = size.y;
+
+        let column = vec![ Cell::default(); self.height as usize ];
+        self.matrices = vec![ vec![ column; self.width as usize ] ];
+        self.floor = 0;
+    }
+
+    //マップの縦横幅とレンジ（外壁含む）
+    pub fn width ( &self ) -> i32 { self.width  }
+    pub fn height( &self ) -> i32 { self.height }
+    pub fn x_range( &self ) -> Range<i32> { 0..self.width  }
+    pub fn y_range( &self ) -> Range<i32> { 0..self.height }
+
+    //外壁を含まないレンジ
+    pub fn x_range_inner( &self ) -> Range<i32> { 1..self.width  - 1 }
+    pub fn y_range_inner( &self ) -> Range<i32> { 1..self.height - 1 }
+
+    //指定の位置がマップの中か（外壁含む）
+    pub fn is_inside( &self, cell: IVec2 ) -> bool
+    {   self.x_range().contains( &cell.x ) &&
+        self.y_range().contains( &cell.y )
+    }
+
+    //cellの四方を調べて空地がある方角のVecを返す
+    pub fn get_sides_space( &self, cell: IVec2 ) -> Vec< News >
+    {   self.get_sides_space_on( self.floor, cell )
+    }
+
+    //指定の階で、cellの四方を調べて空地がある方角のVecを返す
+    pub fn get_sides_space_on( &self, floor: usize, cell: IVec2 ) -> Vec< News >
+    {   //四方の空地を探し記録する
+        let mut sides = Vec::with_capacity( 4 );
+        for news in NEWS
+        {   if self.is_space_on( floor, cell + news ) { sides.push( news ) }
+        }
+
+        sides //空地がある方角のVec
+    }
+
+    //指定の位置の地形を判定する（範囲外は壁）
+    pub fn is_wall( &self, cell: IVec2 ) -> bool
+    {   self.terrain( cell ) == Terrain::Wall
+    }
+    pub fn is_space( &self, cell: IVec2 ) -> bool
+    {   self.terrain( cell ) == Terrain::Space
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//Mapのメソッド（迷路の生成とゴール）
+impl Map
+{   //ゴールの位置を決める
+    //指定がないか、指定の位置にstartからたどり着けなければ、startから最も遠いマスにする
+    pub fn choose_goal( &self ) -> IVec2
+    {   match self.goal_hint
+        {   Some ( goal ) if goal != self.start
+                && self.shortest_path_bfs( self.start, goal ).is_some() => goal,
+            Some ( goal ) =>
+            {   warn!( "Invalid goal {goal}" );
+                self.farthest_cell( self.start )
+            }
+            None => self.farthest_cell( self.start ),
+        }
+    }
+
+    //アルゴリズムがマスクとプレハブに対応しているか（対応しているのは穴掘り法だけ）
+    pub fn check_algorithm( &self ) -> Result<(), String>
+    {   if ( self.mask.is_some() || ! self.prefabs.is_empty() ) && self.algorithm != Algorithm::Digger
+        {   return Err ( format!( "maze mask and prefabs are supported only by {:?}, not {:?}", Algorithm::Digger, self.algorithm ) )
+        }
+        Ok (())
+    }
+
+    //今の乱数で迷路を作り、スタートとゴールを決める
+    //複数階なら、一階から順に作って階段でつなぎ、ゴールは最上階に置く
+    pub fn generate( &mut self )
+    {   if let Some ( log ) = &mut self.build_log { log.clear() } //記録中なら、全部壁の状態から記録する
+
+        //マスクとプレハブに対応していないアルゴリズムなら、穴掘り法に替える（使ったものをalgorithmに残す）
+        if let Err ( err ) = self.check_algorithm()
+        {   warn!( "{err}" );
+            self.algorithm = Algorithm::Digger;
+        }
+
+        let floors = self.floors.clamp( 1, MAP_FLOORS_MAX );
+        self.matrices.truncate( 1 );
+        self.matrices.resize( floors, self.matrices[ 0 ].clone() );
+        self.goal = None;
+
+        //スタート地点を決める（中央付近の奇数座標。区画迷路のアルゴリズムでも使えるように）
+        //マスクがあれば、その内側で中央に一番近いマスにする
+        let center = IVec2::new( ( self.width / 2 ) | 1, ( self.height / 2 ) | 1 );
+        self.start = if self.mask.is_some() { self.masked_start().unwrap_or( center ) } else { center };
+
+        //上の階は、下の階の上り階段の真上から作り始める（startを一時的に借りる）
+        let mut start = self.start;
+        for floor in 0..floors
+        {   self.floor = floor;
+            self.generate_floor( floor == 0, floor + 1 == floors );
+            if floor == 0 { start = self.start } //プレハブでスタートが変わることがある
+
+            if floor > 0
+            {   let stairs = self.start;
+                self.add_flag( stairs, Flag::StairsDown );
+                self.floor -= 1;
+                self.add_flag( stairs, Flag::StairsUp );
+                self.floor += 1;
+            }
+            if floor + 1 < floors { self.start = self.farthest_node( self.start ) }
+        }
+
+        //ゴールは最上階の、上って来た階段から遠いマスにする
+        //（プレハブのゴールでも、たどり着けなければ選び直す）
+        let is_reachable = self.goal.is_some_and( | goal | self.shortest_path_bfs( self.start, goal ).is_some() );
+        if ! is_reachable { self.goal = Some ( self.choose_goal() ) }
+        self.goal_floor = floors - 1;
+        self.start = start;
+        self.floor = 0;
+    }
+
+    //今の階の迷路を作る（startから作り始める）
+    //プレハブは、スタートを含むものを一階に、それ以外を最上階に置く
+    //プレハブを迷路につなげなければ、プレハブを置き直して作り直す
+    fn generate_floor( &mut self, is_first: bool, is_last: bool )
+    {   let ( start, goal ) = ( self.start, self.goal );
+        let log_len = self.build_log.as_ref().map_or( 0, Vec::len );
+        for retry in 0..=PREFAB_RETRIES
+        {   //作り直すなら、スタートとゴールと手順の記録を戻す
+            ( self.start, self.goal ) = ( start, goal );
+            if let Some ( log ) = &mut self.build_log { log.truncate( log_len ) }
+            self.fill_walls();
+
+            //プレハブを置く（スタートやゴールを含むプレハブがあれば、そこがスタートやゴールになる）
+            let placed = self.place_prefabs( is_first, is_last );
+            if ! self.is_space( self.start ) { self.set_space( self.start ) } //プレハブのフラグは消さない
+
+            //迷路を作る（アルゴリズムは差し替え可能）
+            self.algorithm.generator().build( self );
+            if self.connect_prefabs( &placed ) { break }
+            if retry == PREFAB_RETRIES { warn!( "Prefab could not be connected" ) }
+        }
+        self.braid( self.braid ); //ブレイド率が0.0なら完全迷路のまま
+    }
+}
+
+//ゴールの位置を得る
+//コマンドライン引数か設定ファイルで指定できる（例：--goal 1,1）
+pub fn goal_setting() -> Option<IVec2>
+{   let goal = misc::setting( "goal" )?;
+    parse_cell( &goal ).or_else( || { warn!( "Invalid goal \"{goal}\"" ); None } )
+}
+
+//"x,y"形式の文字列からマスの位置を得る
+pub fn parse_cell( s: &str ) -> Option<IVec2>
+{   let ( x, y ) = s.split_once( ',' )?;
+    Some ( IVec2::new( x.trim().parse().ok()?, y.trim().parse().ok()? ) )
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//迷路を作る手順の一歩（見物モードで再生する）
+#[derive( Clone, Copy, PartialEq, Eq, Debug )]
+pub enum BuildStep
+{   Dig  ( IVec2 ),      //壁を掘って空地にした
+    Fill ( IVec2 ),      //空地を埋めて壁にした
+    Backtrack ( IVec2 ), //穴掘り法で、行き止りから戻り道へ進んだ
+}
+
+//穴掘り法を一歩ずつ進める状態機械（一歩ごとにBuildStepを返すIterator）
+pub struct Labyrinth<'a>
+{   map : &'a mut Map,
+    cell: IVec2,               //穴を掘っている位置
+    digable_walls: Vec<IVec2>, //四方の掘れる壁（使い回す）
+}
+
+impl Iterator for Labyrinth<'_>
+{   type Item = BuildStep;
+
+    //一歩進める（掘れる壁も戻り道もなければ迷路完成でNone）
+    fn next( &mut self ) -> Option<BuildStep>
+    {   //四方の判定準備
+        let map = &mut *self.map;
+        let cell = self.cell;
+        self.digable_walls.clear();
+        let mut backtrack = IVec2::NEG_ONE;
+
+        //四方の掘れる壁と戻り道を記録する
+        for news in NEWS
+        {   let next = cell + news;
+
+            //外壁は掘れない
+            if ! map.x_range_inner().contains( &next.x )
+            || ! map.y_range_inner().contains( &next.y ) { continue }
+
+            //四方のグリッドを調べる
+            if map.is_wall( next ) && map.is_digable( next, news )
+            {   //壁であり且つ掘れるなら
+                self.digable_walls.push( next );
+            }
+            else if map.is_space( next ) && ! map.has_flag( next, Flag::DeadEnd )
+            {   //道であり且つ行止りのマーキングがないなら
+                backtrack = next;
+            }
+        }
+
+        if ! self.digable_walls.is_empty()
+        {   //掘れる壁が見つかったので、方向をランダムに決めて進む
+            self.cell = self.digable_walls[ map.rng.gen_range( 0..self.digable_walls.len() ) ];
+            map.set_space( self.cell );
+            Some ( BuildStep::Dig ( self.cell ) )
+        }
+        else
+        {   //掘れる壁が見つからず、戻り道も見つからないなら迷路完成
+            if backtrack == IVec2::NEG_ONE { return None }
+
+            //現在位置に行き止まりをマークし、戻り路へ進む(後戻りする)
+            map.add_flag( cell, Flag::DeadEnd );
+            self.cell = backtrack;
+            map.log_step( BuildStep::Backtrack ( backtrack ) );
+            Some ( BuildStep::Backtrack ( backtrack ) )
+        }
+    }
+}
+
+//Mapのメソッド（迷路作成）
+impl Map
+{   //穴掘り法を一歩ずつ進める状態機械を作る
+    //（穴を掘る準備。スタートがプレハブの中なら、プレハブの外から掘り始める）
+    pub fn labyrinth( &mut self ) -> Labyrinth<'_>
+    {   let cell = self.labyrinth_origin();
+        Labyrinth { map: self, cell, digable_walls: Vec::new() }
+    }
+
+    //迷路作成メソッド（穴掘り法を最後まで進める）
+    fn build_labyrinth( &mut self )
+    {   self.labyrinth().for_each( drop );
+    }
+
+    //壁が掘れるか調べる（マスクの外とプレハブは掘れない）
+    fn is_digable( &self, cell: IVec2, news: News ) -> bool
+    {   if ! self.is_in_mask( cell ) || self.has_flag( cell, Flag::Prefab ) { return false }
+
+        match news
+        {   News::North
+            if self.is_wall( cell + News::North + News::West )
+            && self.is_wall( cell + News::North              ) // 壁壁壁
+            && self.is_wall( cell + News::North + News::East ) // 壁？壁
+            && self.is_wall( cell + News::West               )
+            && self.is_wall( cell + News::East               )
+                => true,
+            News::West
+            if self.is_wall( cell + News::North + News::West )
+            && self.is_wall( cell + News::North              ) // 壁壁
+            && self.is_wall( cell + News::West               ) // 壁？◎
+            && self.is_wall( cell + News::South + News::West ) // 壁壁
+            && self.is_wall( cell + News::South              )
+                => true,
+            News::East
+            if self.is_wall( cell + News::North              )
+            && self.is_wall( cell + News::North + News::East ) // 　壁壁
+            && self.is_wall( cell + News::East               ) // ◎？壁
+            && self.is_wall( cell + News::South              ) // 　壁壁
+            && self.is_wall( cell + News::South + News::East )
+                => true,
+            News::South
+            if self.is_wall( cell + News::West               )
+            && self.is_wall( cell + News::East               ) // 　◎
+            && self.is_wall( cell + News::South + News::West ) // 壁？壁
+            && self.is_wall( cell + News::South              ) // 壁壁壁
+            && self.is_wall( cell + News::South + News::East )
+                => true,
+            _   => false,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
\ No newline at end of file
@@ -0,0 +1,316 @@
+use super::*;
+
+//standard library
+use std::fmt;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//迷路のテキスト形式（SCREEN_FRAME.designと同じく一文字が一マス）
+//  '#' 壁          '.' 空地        ':' 空地(行き止りフラグ付き)
+//  'S' スタート    's' スタート(行き止りフラグ付き)
+//  'G' ゴール      'g' ゴール(行き止りフラグ付き)
+//  '<' 上り階段    '>' 下り階段    '?' 未定義
+//"//"で始まる行は読み飛ばす。空行で区切ると次の階になる（一階から順に。スタートは一階）
+//上り階段の真上のマスは、上の階の下り階段にする
+//'@'で始まる行は、文字で表せないマスのフラグと部屋番号（例：「@1,3,5 Visited Prefab room=2」階は1から数える）
+pub const ASCII_WALL         : char = '#';
+pub const ASCII_SPACE        : char = '.';
+pub const ASCII_SPACE_DEADEND: char = ':';
+pub const ASCII_START        : char = 'S';
+pub const ASCII_START_DEADEND: char = 's';
+pub const ASCII_GOAL         : char = 'G';
+pub const ASCII_GOAL_DEADEND : char = 'g';
+pub const ASCII_STAIRS_UP    : char = '<';
+pub const ASCII_STAIRS_DOWN  : char = '>';
+pub const ASCII_UNDEF        : char = '?';
+pub const ASCII_COMMENT      : &str = "//";
+pub const ASCII_FLAGS        : char = '@';
+pub const ASCII_ROOM         : &str = "room=";
+
+//テキストの読込みエラー（行と桁は1から数える）
+#[derive( Clone, PartialEq, Eq, Debug )]
+pub struct MazeParseError
+{   pub line  : usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for MazeParseError
+{   fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result
+    {   write!( f, "line {}, column {}: {}", self.line, self.column, self.message )
+    }
+}
+
+impl std::error::Error for MazeParseError {}
+
+//テキストから読んだ迷路（設定を読まない純粋なデータ。Map::install_layout()でMapに入れる）
+#[derive( Clone, PartialEq, Eq, Debug )]
+pub struct MazeLayout
+{   size: IVec2,                   //縦横幅(外壁含む)
+    matrices: Vec<Vec<Vec<Cell>>>, //階ごとのマス
+    start: IVec2,                  //スタート位置（一階）
+    goal : Option<( usize, IVec2 )>, //ゴールの階と位置
+}
+
+impl MazeLayout
+{   //テキストを読む（一時的なMapの上で地形とフラグを組み立てる）
+    pub fn parse( text: &str ) -> Result<Self, MazeParseError>
+    {   let error = | line, column, message: String | MazeParseError { line, column, message };
+
+        //コメントを除いた行を、空行で区切って階ごとに集める（行番号付き）
+        let mut floors: Vec<Vec<( usize, &str )>> = Vec::new();
+        let mut rows = Vec::new();
+        let mut flag_lines = Vec::new();
+        for ( i, line ) in text.lines().chain( [ "" ] ).enumerate()
+        {   let line = line.trim_end();
+            if line.starts_with( ASCII_COMMENT ) { continue }
+            if line.starts_with( ASCII_FLAGS ) { flag_lines.push( ( i + 1, line ) ); continue }
+            if ! line.is_empty() { rows.push( ( i + 1, line ) ); continue }
+            if ! rows.is_empty() { floors.push( std::mem::take( &mut rows ) ) }
+        }
+
+        //縦横幅と階数を調べる
+        let Some ( &( first_line, first_row ) ) = floors.first().map( | rows | &rows[ 0 ] )
+        else { return Err ( error( 1, 1, "no map rows".to_string() ) ) };
+        let width  = first_row.chars().count() as i32;
+        let height = floors[ 0 ].len() as i32;
+        let range = MAP_GRIDS_MIN..=MAP_GRIDS_MAX;
+        if ! range.contains( &width ) || ! range.contains( &height )
+        {   let message = format!( "map size {width}x{height} is out of range {range:?}" );
+            return Err ( error( first_line, 1, message ) );
+        }
+        if floors.len() > MAP_FLOORS_MAX
+        {   let message = format!( "{} floors exceed the limit {MAP_FLOORS_MAX}", floors.len() );
+            return Err ( error( floors[ MAP_FLOORS_MAX ][ 0 ].0, 1, message ) );
+        }
+
+        //一文字ずつ読む
+        let mut map = Map::new( IVec2::new( width, height ), Algorithm::default() );
+        map.matrices.resize( floors.len(), map.matrices[ 0 ].clone() );
+        let ( mut start, mut goal ) = ( None, None );
+        let mut stairs = Vec::new();
+        for ( floor, rows ) in floors.iter().enumerate()
+        {   map.floor = floor;
+            if rows.len() != height as usize
+            {   let message = format!( "floor {} has {} rows, differs from map height {height}", floor + 1, rows.len() );
+                return Err ( error( rows[ 0 ].0, 1, message ) );
+            }
+
+            for ( y, &( line, row ) ) in rows.iter().enumerate()
+            {   let length = row.chars().count();
+                if length != width as usize
+                {   let message = format!( "row length {length} differs from map width {width}" );
+                    return Err ( error( line, length.min( width as usize ) + 1, message ) );
+                }
+
+                for ( x, char ) in row.chars().enumerate()
+                {   let cell = IVec2::new( x as i32, y as i32 );
+                    let column = x + 1;
+
+                    //スタートとゴールは一つまで（スタートは一階だけ）
+                    let marker = match char
+                    {   ASCII_START | ASCII_START_DEADEND => Some ( ( &mut start, "start" ) ),
+                        ASCII_GOAL  | ASCII_GOAL_DEADEND  => Some ( ( &mut goal , "goal"  ) ),
+                        _ => None,
+                    };
+                    if let Some ( ( position, name ) ) = marker
+                    {   if position.is_some()
+                        {   return Err ( error( line, column, format!( "duplicate {name} '{char}'" ) ) );
+                        }
+                        if name == "start" && floor > 0
+                        {   return Err ( error( line, column, format!( "start '{char}' must be on the first floor" ) ) );
+                        }
+                        *position = Some ( ( floor, cell ) );
+                    }
+
+                    let Some ( c ) = ascii_cell( char )
+                    else { return Err ( error( line, column, format!( "unknown map char '{char}'" ) ) ) };
+                    *map.matrix_mut( cell ) = c;
+                    if char == ASCII_STAIRS_UP || char == ASCII_STAIRS_DOWN { stairs.push( ( floor, cell, line, column ) ) }
+                }
+            }
+        }
+
+        //文字で表せないフラグと部屋番号を足す（「@階,x,y フラグ名 … room=番号」）
+        for ( line, text ) in flag_lines
+        {   let mut column = ASCII_FLAGS.len_utf8() + 1;
+            let mut words = text[ ASCII_FLAGS.len_utf8().. ].split( ' ' ).map
+            (   | word |
+                {   let word_column = column;
+                    column += word.chars().count() + 1;
+                    ( word_column, word )
+                }
+            )
+            .filter( | ( _, word ) | ! word.is_empty() );
+
+            //位置
+            let Some ( ( position_column, position ) ) = words.next()
+            else { return Err ( error( line, 1, "no cell position".to_string() ) ) };
+            let numbers: Vec<_> = position.split( ',' ).map( | n | n.parse::<usize>().ok() ).collect();
+            let ( floor, cell ) = match numbers[ .. ]
+            {   [ Some ( floor ), Some ( x ), Some ( y ) ] if ( 1..=map.floor_count() ).contains( &floor ) =>
+                    ( floor - 1, IVec2::new( x as i32, y as i32 ) ),
+                _ => return Err ( error( line, position_column, format!( "invalid cell position \"{position}\"" ) ) ),
+            };
+            if ! map.is_inside( cell )
+            {   return Err ( error( line, position_column, format!( "cell position \"{position}\" is outside the map" ) ) );
+            }
+
+            //フラグと部屋番号
+            map.floor = floor;
+            for ( column, word ) in words
+            {   if let Some ( id ) = word.strip_prefix( ASCII_ROOM )
+                {   match id.parse::<u16>()
+                    {   Ok ( id ) if id < ROOM_MAX => map.set_room( cell, id ),
+                        _ => return Err ( error( line, column, format!( "invalid room id \"{id}\"" ) ) ),
+                    }
+                    continue
+                }
+                let Some ( flag ) = FLAGS.iter().copied().find( | flag | format!( "{flag:?}" ) == word )
+                else { return Err ( error( line, column, format!( "unknown flag \"{word}\"" ) ) ) };
+                map.add_flag( cell, flag );
+                if flag == Flag::StairsUp || flag == Flag::StairsDown { stairs.push( ( floor, cell, line, column ) ) }
+            }
+        }
+
+        //階段は上下の階で対になっていること
+        for ( floor, cell, line, column ) in stairs
+        {   let is_up = map.cell_on( floor, cell ).is_some_and( | c | c.flags.contains( Flag::StairsUp ) );
+            let ( o_pair_floor, pair ) = if is_up
+            {   ( Some ( floor + 1 ), Flag::StairsDown )
+            }
+            else
+            {   ( floor.checked_sub( 1 ), Flag::StairsUp )
+            };
+            let is_paired = o_pair_floor
+                .and_then( | pair_floor | map.cell_on( pair_floor, cell ) )
+                .is_some_and( | c | c.flags.contains( pair ) );
+            if ! is_paired
+            {   let message = if is_up
+                {   format!( "stairs up '{ASCII_STAIRS_UP}' have no stairs down '{ASCII_STAIRS_DOWN}' above" )
+                }
+                else
+                {   format!( "stairs down '{ASCII_STAIRS_DOWN}' have no stairs up '{ASCII_STAIRS_UP}' below" )
+                };
+                return Err ( error( line, column, message ) );
+            }
+        }
+
+        //スタートは必須
+        let Some ( ( _, start ) ) = start
+        else { return Err ( error( first_line, 1, format!( "start '{ASCII_START}' not found" ) ) ) };
+
+        Ok ( Self { size: IVec2::new( width, height ), matrices: map.matrices, start, goal } )
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//Mapのpubメソッド（テキスト形式の書き出しと読込み）
+impl Map
+{   //Mapをテキストにする
+    pub fn to_ascii( &self ) -> String
+    {   let mut text = format!( "{ASCII_COMMENT}{APP_TITLE} maze {}x{}", self.width, self.height );
+        if self.floor_count() > 1 { text += &format!( " {} floors", self.floor_count() ) }
+        text.push( '\n' );
+
+        for floor in 0..self.floor_count()
+        {   if floor > 0 { text.push( '\n' ) } //階の区切り
+            for y in self.y_range()
+            {   for x in self.x_range()
+                {   text.push( self.ascii_char( floor, IVec2::new( x, y ) ) );
+                }
+                text.push( '\n' );
+            }
+        }
+
+        //文字で表せないフラグと部屋番号を、マスごとに一行で書く
+        let mut flags_text = String::new();
+        for floor in 0..self.floor_count()
+        {   for y in self.y_range()
+            {   for x in self.x_range()
+                {   let cell = IVec2::new( x, y );
+                    let Some ( c ) = self.cell_on( floor, cell ) else { continue };
+                    let Some ( implied ) = ascii_cell( self.ascii_char( floor, cell ) ) else { continue };
+                    let extra: Vec<_> = c.flags.iter().filter( | &flag | ! implied.flags.contains( flag ) ).collect();
+                    if extra.is_empty() && c.flags.room().is_none() { continue }
+
+                    flags_text += &format!( "{ASCII_FLAGS}{},{x},{y}", floor + 1 );
+                    extra.iter().for_each( | flag | flags_text += &format!( " {flag:?}" ) );
+                    if let Some ( id ) = c.flags.room() { flags_text += &format!( " {ASCII_ROOM}{id}" ) }
+                    flags_text.push( '\n' );
+                }
+            }
+        }
+        if ! flags_text.is_empty() { text += &format!( "\n{flags_text}" ) }
+
+        text
+    }
+
+    //テキストからMapを作る（エラーの場合Mapは変更されない）
+    pub fn load_ascii( &mut self, text: &str ) -> Result<(), MazeParseError>
+    {   let layout = MazeLayout::parse( text )?;
+        self.install_layout( &layout );
+        Ok (())
+    }
+
+    //読んだ迷路をMapに入れる（乱数・マスク・プレハブ等の設定はそのまま）
+    pub fn install_layout( &mut self, layout: &MazeLayout )
+    {   self.width  = layout.size.x;
+        self.height = layout.size.y;
+        self.matrices = layout.matrices.clone();
+        self.start = layout.start;
+        self.goal  = layout.goal.map( | ( _, goal ) | goal );
+        self.goal_floor = layout.goal.map_or( 0, | ( floor, _ ) | floor );
+        self.floors = self.floor_count();
+        self.floor  = 0;
+        if let Some ( log ) = &mut self.build_log { log.clear() } //読み込んだ迷路に手順はない
+    }
+
+    //指定の階のマスの状態を一文字にする（スタートは一階、ゴールはゴールの階だけ）
+    //（一文字で表せないフラグは、to_ascii()が'@'の行に書く）
+    fn ascii_char( &self, floor: usize, cell: IVec2 ) -> char
+    {   let Some ( &Cell { terrain, flags } ) = self.cell_on( floor, cell ) else { return ASCII_UNDEF };
+        let is_deadend = flags.contains( Flag::DeadEnd );
+        if floor == 0 && cell == self.start
+        {   if is_deadend { ASCII_START_DEADEND } else { ASCII_START }
+        }
+        else if floor == self.goal_floor && Some ( cell ) == self.goal
+        {   if is_deadend { ASCII_GOAL_DEADEND } else { ASCII_GOAL }
+        }
+        else if flags.contains( Flag::StairsUp   ) { ASCII_STAIRS_UP   }
+        else if flags.contains( Flag::StairsDown ) { ASCII_STAIRS_DOWN }
+        else
+        {   match terrain
+            {   Terrain::Space if is_deadend => ASCII_SPACE_DEADEND,
+                Terrain::Space => ASCII_SPACE,
+                Terrain::Wall  => ASCII_WALL,
+                Terrain::Undef => ASCII_UNDEF,
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//一文字が表すマス（地形と、文字に含まれるフラグ）
+fn ascii_cell( char: char ) -> Option<Cell>
+{   let ( terrain, o_flag ) = match char
+    {   ASCII_WALL  => ( Terrain::Wall , None ),
+        ASCII_UNDEF => ( Terrain::Undef, None ),
+        ASCII_SPACE | ASCII_START | ASCII_GOAL => ( Terrain::Space, None ),
+        ASCII_SPACE_DEADEND | ASCII_START_DEADEND | ASCII_GOAL_DEADEND =>
+            ( Terrain::Space, Some ( Flag::DeadEnd ) ),
+        ASCII_STAIRS_UP   => ( Terrain::Space, Some ( Flag::StairsUp   ) ),
+        ASCII_STAIRS_DOWN => ( Terrain::Space, Some ( Flag::StairsDown ) ),
+        _ => return None,
+    };
+
+    let mut cell = Cell::new( terrain );
+    if let Some ( flag ) = o_flag { cell.flags.insert( flag ) }
+    Some ( cell )
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
\ No newline at end of file
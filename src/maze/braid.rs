@@ -0,0 +1,90 @@
+use super::*;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//ブレイド率を得る（0.0なら完全迷路のまま、1.0なら行き止りをできるだけ無くす）
+//コマンドライン引数か設定ファイルで指定できる（例：--braid 0.5）
+pub fn braid_setting() -> f32
+{   misc::setting( "braid" ).map_or
+    (   0.0,
+        | braid | parse_braid( &braid ).unwrap_or_else
+        (   || { warn!( "Invalid braid factor \"{braid}\"" ); 0.0 }
+        )
+    )
+}
+
+//0.0～1.0の文字列からブレイド率を得る
+pub fn parse_braid( s: &str ) -> Option<f32>
+{   let braid = s.trim().parse::<f32>().ok()?;
+    ( 0.0..=1.0 ).contains( &braid ).then_some( braid )
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//Mapのメソッド（ブレイド迷路）
+impl Map
+{   //行き止り(四方の空地が一つ)の一覧
+    pub fn dead_end_cells( &self ) -> Vec<IVec2>
+    {   self.cells_where( | c | c.terrain == Terrain::Space )
+        .filter( | &cell | self.get_sides_space( cell ).len() == 1 )
+        .collect()
+    }
+
+    //行き止りの壁を壊して、向こう側の空地とつなげられる方角の一覧
+    //（壊す壁の左右が壁の場合だけにして、道幅が広がらないようにする。外壁とマスクの外とプレハブは壊さない）
+    pub fn breakable_sides( &self, cell: IVec2 ) -> Vec<News>
+    {   NEWS.into_iter()
+        .filter
+        (   | &news |
+            {   let wall = cell + news;
+                self.is_in_mask( wall )
+                && self.is_wall( wall )
+                && ! self.has_flag( wall, Flag::Prefab )
+                && self.is_space( wall + news )
+                && self.is_wall( wall + news.turn_right() )
+                && self.is_wall( wall + news.turn_left()  )
+            }
+        )
+        .collect()
+    }
+
+    //行き止りのうち、ブレイド率の割合の壁を壊してループを作る
+    pub fn braid( &mut self, factor: f32 )
+    {   if factor <= 0.0 { return } //完全迷路のまま（乱数も消費しない）
+
+        let mut dead_ends = self.dead_end_cells();
+        dead_ends.shuffle( &mut self.rng );
+        let count = ( dead_ends.len() as f32 * factor.min( 1.0 ) ).round() as usize;
+        dead_ends.truncate( count );
+
+        //壁を壊すと、先に壊せなかった行き止りが壊せるようになることがあるので、
+        //壊せるものがなくなるまで繰り返す
+        loop
+        {   let before = dead_ends.len();
+            dead_ends.retain( | &cell | ! self.break_dead_end( cell ) );
+            if dead_ends.len() == before { break }
+        }
+    }
+
+    //行き止りの壁を一つ壊す（行き止りでなくなったらtrue）
+    fn break_dead_end( &mut self, cell: IVec2 ) -> bool
+    {   //先に壊した壁で行き止りでなくなっていたら何もしない
+        if self.get_sides_space( cell ).len() != 1 { return true }
+
+        let sides = self.breakable_sides( cell );
+        if sides.is_empty() { return false }
+
+        //向こう側も行き止りなら優先して、二つ同時に解消する
+        let ( twins, others ): ( Vec<_>, Vec<_> ) = sides.into_iter()
+            .partition( | &news | self.get_sides_space( cell + news + news ).len() == 1 );
+        let candidates = if twins.is_empty() { others } else { twins };
+        let news = candidates[ self.rng.gen_range( 0..candidates.len() ) ];
+
+        self.set_space( cell + news );
+        true
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
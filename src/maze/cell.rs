@@ -0,0 +1,169 @@
+use super::*;
+
+//standard library
+use std::fmt;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//マスの地形
+#[derive( Clone, Copy, PartialEq, Eq, Debug, Default )]
+pub enum Terrain
+{   #[default] Undef, //未定義
+    Space,            //空地
+    Wall,             //壁
+}
+
+//フラグのenumと、その一覧FLAGS（宣言順）を一緒に定義するマクロ
+//（一覧はenumから作られるので、フラグを足しても一覧の更新漏れは起きない）
+macro_rules! define_flags
+{   ( $( $flag: ident, )* ) =>
+    {   #[derive( Clone, Copy, PartialEq, Eq, Debug )]
+        pub enum Flag { $( $flag, )* }
+
+        //フラグの一覧（FlagSetのiter()用）
+        pub const FLAGS: &[ Flag ] = &[ $( Flag::$flag, )* ];
+    };
+}
+
+//マスに付加するフラグ
+//（新しいフラグはここに追加するだけでよい。ビット位置は宣言順で決まる。16個まで）
+define_flags!
+{   DeadEnd,    //行き止り（穴掘り法では後戻りした印）
+    Visited,    //Playerが通ったマス
+    Seen,       //Playerから見えたマス
+    Prefab,     //プレハブの区画とその周り（穴掘り法では掘らない）
+    StairsUp,   //上の階へ上る階段（真上のマスは下り階段）
+    StairsDown, //下の階へ下りる階段（真下のマスは上り階段）
+}
+
+impl Flag
+{   fn bit( self ) -> u32 { 1 << self as u32 }
+}
+
+//部屋番号の位置（FlagSetの上位16ビット。0は部屋ではない）
+const ROOM_SHIFT: u32 = 16;
+const FLAGS_MASK: u32 = ( 1 << ROOM_SHIFT ) - 1;
+pub const ROOM_MAX: u16 = u16::MAX; //部屋番号はこれ未満（+1して持つため）
+const _: () = assert!( FLAGS.len() <= ROOM_SHIFT as usize ); //フラグが多すぎたらコンパイルエラー
+
+//フラグの集合と部屋番号（一マス4バイト）
+#[derive( Clone, Copy, PartialEq, Eq, Default )]
+pub struct FlagSet ( u32 );
+
+impl FlagSet
+{   pub fn contains( &self, flag: Flag ) -> bool { self.0 & flag.bit() != 0 }
+    pub fn insert  ( &mut self, flag: Flag ) { self.0 |= flag.bit() }
+    pub fn remove  ( &mut self, flag: Flag ) { self.0 &= ! flag.bit() }
+
+    //部屋番号（ダンジョンの部屋のマスだけSome）
+    pub fn room( &self ) -> Option<u16>
+    {   match ( self.0 >> ROOM_SHIFT ) as u16
+        {   0 => None,
+            id => Some ( id - 1 ),
+        }
+    }
+    pub fn set_room( &mut self, id: u16 )
+    {   debug_assert!( id < ROOM_MAX, "room id {id} is too large" );
+        let id = id as u32 + 1; //0は部屋ではない印
+        self.0 = self.0 & FLAGS_MASK | id << ROOM_SHIFT;
+    }
+
+    //含まれるフラグを列挙する
+    pub fn iter( &self ) -> impl Iterator<Item = Flag> + '_
+    {   FLAGS.iter().copied().filter( | &flag | self.contains( flag ) )
+    }
+}
+
+impl fmt::Debug for FlagSet
+{   fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result
+    {   if let Some ( id ) = self.room() { write!( f, "room {id} " )? }
+        f.debug_set().entries( self.iter() ).finish()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//マスの情報
+#[derive( Clone, Copy, PartialEq, Eq, Debug, Default )]
+pub struct Cell
+{   pub terrain: Terrain,
+    pub flags  : FlagSet,
+}
+
+impl Cell
+{   //指定の地形でフラグのないマス
+    pub const fn new( terrain: Terrain ) -> Self
+    {   Self { terrain, flags: FlagSet ( 0 ) }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//Mapのpubメソッド（マスの地形とフラグ）
+impl Map
+{   //指定の位置の地形（範囲外は壁にする）
+    pub fn terrain( &self, cell: IVec2 ) -> Terrain
+    {   if ! self.is_inside( cell ) { return Terrain::Wall }
+        self.matrix( cell ).terrain
+    }
+
+    //指定の位置のフラグ（範囲外は空）
+    pub fn flags( &self, cell: IVec2 ) -> FlagSet
+    {   if ! self.is_inside( cell ) { return FlagSet::default() }
+        self.matrix( cell ).flags
+    }
+
+    //指定の階の指定の位置のマス（範囲外はNone。今の階を変えずに読む）
+    pub fn cell_on( &self, floor: usize, cell: IVec2 ) -> Option<&Cell>
+    {   if floor >= self.matrices.len() || ! self.is_inside( cell ) { return None }
+        Some ( &self.matrices[ floor ][ cell.x as usize ][ cell.y as usize ] )
+    }
+
+    //指定の階の指定の位置の地形を判定する（範囲外は壁）
+    pub fn is_wall_on( &self, floor: usize, cell: IVec2 ) -> bool
+    {   self.cell_on( floor, cell ).is_none_or( | c | c.terrain == Terrain::Wall )
+    }
+    pub fn is_space_on( &self, floor: usize, cell: IVec2 ) -> bool
+    {   self.cell_on( floor, cell ).is_some_and( | c | c.terrain == Terrain::Space )
+    }
+
+    //指定の位置にフラグがあるか判定する
+    pub fn has_flag( &self, cell: IVec2, flag: Flag ) -> bool
+    {   self.flags( cell ).contains( flag )
+    }
+
+    //指定の位置にフラグを付加する
+    pub fn add_flag( &mut self, cell: IVec2, flag: Flag )
+    {   if ! self.is_inside( cell ) { return }
+        self.matrix_mut( cell ).flags.insert( flag );
+    }
+
+    //指定の位置の部屋番号（部屋でなければNone）
+    pub fn room( &self, cell: IVec2 ) -> Option<u16>
+    {   self.flags( cell ).room()
+    }
+
+    //指定の位置に部屋番号を付ける
+    pub fn set_room( &mut self, cell: IVec2, id: u16 )
+    {   if ! self.is_inside( cell ) { return }
+        self.matrix_mut( cell ).flags.set_room( id );
+    }
+
+    //条件に合うマスを列挙する（例：map.cells_where( | c | c.flags.contains( Flag::DeadEnd ) )）
+    pub fn cells_where<F>( &self, filter: F ) -> impl Iterator<Item = IVec2> + '_
+    where F: Fn( &Cell ) -> bool + 'static
+    {   self.cells_where_on( self.floor, filter )
+    }
+
+    //指定の階で、条件に合うマスを列挙する
+    pub fn cells_where_on<F>( &self, floor: usize, filter: F ) -> impl Iterator<Item = IVec2> + '_
+    where F: Fn( &Cell ) -> bool + 'static
+    {   self.x_range()
+        .flat_map( move | x | self.y_range().map( move | y | IVec2::new( x, y ) ) )
+        .filter( move | &cell | self.cell_on( floor, cell ).is_some_and( &filter ) )
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
\ No newline at end of file
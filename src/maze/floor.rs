@@ -0,0 +1,94 @@
+use super::*;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//迷路の階数を得る
+//コマンドライン引数か設定ファイルで指定できる（例：--floors 3）
+pub fn floors_setting() -> usize
+{   let Some ( floors ) = misc::setting( "floors" ) else { return 1 };
+    parse_floors( &floors ).unwrap_or_else( || { warn!( "Invalid floors \"{floors}\"" ); 1 } )
+}
+
+//階数の文字列を読む（1～MAP_FLOORS_MAX）
+pub fn parse_floors( s: &str ) -> Option<usize>
+{   let floors = s.trim().parse().ok()?;
+    ( 1..=MAP_FLOORS_MAX ).contains( &floors ).then_some( floors )
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//Mapのpubメソッド（階）
+//地形やフラグのメソッドは今の階を扱う。階を変えると、霧やミニマップもその階のものになる
+impl Map
+{   //今の階と階数
+    pub fn floor( &self ) -> usize { self.floor }
+    pub fn floor_count( &self ) -> usize { self.matrices.len() }
+
+    //今の階を変える（範囲外なら最寄りの階にする）
+    pub fn set_floor( &mut self, floor: usize )
+    {   self.floor = floor.min( self.floor_count() - 1 );
+    }
+
+    //今の階の入口（一階はスタート、他の階は下りる階段）
+    pub fn floor_entrance( &self ) -> IVec2
+    {   self.floor_entrance_on( self.floor )
+    }
+    pub fn floor_entrance_on( &self, floor: usize ) -> IVec2
+    {   if floor == 0 { return self.start }
+        self.cells_where_on( floor, | c | c.flags.contains( Flag::StairsDown ) ).next().unwrap_or( self.start )
+    }
+
+    //今の階の出口（ゴールの階はゴール、他の階はゴールの階へ向かう階段）
+    pub fn floor_exit( &self ) -> Option<IVec2>
+    {   self.floor_exit_on( self.floor )
+    }
+    pub fn floor_exit_on( &self, floor: usize ) -> Option<IVec2>
+    {   let stairs = match floor.cmp( &self.goal_floor )
+        {   std::cmp::Ordering::Equal   => return self.goal,
+            std::cmp::Ordering::Less    => Flag::StairsUp,
+            std::cmp::Ordering::Greater => Flag::StairsDown,
+        };
+        self.cells_where_on( floor, move | c | c.flags.contains( stairs ) ).next()
+    }
+
+    //階ごとに分析する（一階からゴールの階まで。今の階は変えない）
+    pub fn floor_stats( &self ) -> Vec<MazeStats>
+    {   ( 0..=self.goal_floor.min( self.floor_count() - 1 ) )
+            .map( | floor | self.stats_on( floor ) )
+            .collect()
+    }
+
+    //スタートからゴールまでの最短歩数（階段の上り下りも一歩と数える。たどり着けなければNone）
+    pub fn solution_length( &self ) -> Option<usize>
+    {   let stats = self.floor_stats();
+        let climbs = stats.len() - 1;
+        stats.iter().map( | stats | stats.solution_length ).sum::<Option<usize>>().map( | n | n + climbs )
+    }
+
+    //迷路全体の難易度の点数（一階からゴールの階までの点数の合計）
+    pub fn difficulty( &self ) -> f32
+    {   self.floor_stats().iter().map( | stats | stats.difficulty ).sum()
+    }
+
+    //指定の位置から道なりに最も遠い、奇数座標のマス（区画迷路のアルゴリズムでも作り始められるように）
+    //奇数座標のマスにたどり着けなければ、最も遠いマス
+    pub fn farthest_node( &self, from: IVec2 ) -> IVec2
+    {   let is_node = | cell: IVec2 | cell.x % 2 == 1 && cell.y % 2 == 1;
+        let mut visited = HashSet::from( [ from ] );
+        let mut queue = VecDeque::from( [ from ] );
+        let mut farthest = None;
+        while let Some ( cell ) = queue.pop_front()
+        {   if is_node( cell ) { farthest = Some ( cell ) } //最後に取り出したものが最も遠い
+            for news in self.get_sides_space( cell )
+            {   let next = cell + news;
+                if visited.insert( next ) { queue.push_back( next ) }
+            }
+        }
+
+        farthest.unwrap_or_else( || self.farthest_cell( from ) )
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
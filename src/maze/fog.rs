@@ -0,0 +1,46 @@
+use super::*;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//Mapのpubメソッド（探索の記録）
+//Flag::Seenは見えたマス(壁を含む)、Flag::VisitedはPlayerが通ったマス
+impl Map
+{   //指定の位置に立ったときの視界を記録する
+    //視界は通路に沿った直線の見通し：四方へ壁に当たるまで進み、途中のマスの両脇も見える
+    pub fn update_fog( &mut self, from: IVec2 )
+    {   if ! self.is_inside( from ) { return }
+        self.add_flag( from, Flag::Visited );
+        self.add_flag( from, Flag::Seen );
+
+        //足元の周囲8マスは常に見える
+        for dx in -1..=1
+        {   for dy in -1..=1
+            {   self.add_flag( from + IVec2::new( dx, dy ), Flag::Seen );
+            }
+        }
+
+        for news in NEWS
+        {   let mut cell = from + news;
+            while self.is_space( cell )
+            {   self.add_flag( cell, Flag::Seen );
+                self.add_flag( cell + news.turn_right(), Flag::Seen );
+                self.add_flag( cell + news.turn_left() , Flag::Seen );
+                cell += news;
+            }
+            self.add_flag( cell, Flag::Seen ); //突き当たりの壁
+        }
+    }
+
+    //見えた空地の割合(0.0～1.0)
+    pub fn explored_ratio( &self ) -> f32
+    {   let open = self.cells_where( | c | c.terrain == Terrain::Space ).count();
+        let seen = self.cells_where( | c | c.terrain == Terrain::Space && c.flags.contains( Flag::Seen ) ).count();
+        if open == 0 { return 0.0 }
+
+        seen as f32 / open as f32
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
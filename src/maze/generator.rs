@@ -0,0 +1,403 @@
+use super::*;
+
+//standard library
+use std::collections::HashMap;
+use std::str::FromStr;
+
+//internal submodules
+mod dungeon;
+mod cave;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//迷路生成アルゴリズムのTrait
+//（Mapが壁で埋め尽くされ、startだけ空地になった状態で呼び出される）
+pub trait MazeGenerator
+{   fn build( &self, map: &mut Map );
+}
+
+//迷路生成アルゴリズムの種類
+#[derive( Clone, Copy, Default, PartialEq, Eq, Debug )]
+pub enum Algorithm
+{   #[default] Digger,
+    Prim,
+    Kruskal,
+    Wilson,
+    Eller,
+    HuntAndKill,
+    Sidewinder,
+    RecursiveDivision,
+    Dungeon,
+    Cave,
+}
+
+impl Algorithm
+{   //アルゴリズムに対応する生成器を返す
+    pub fn generator( &self ) -> &'static dyn MazeGenerator
+    {   match self
+        {   Algorithm::Digger            => &Digger,
+            Algorithm::Prim              => &Prim,
+            Algorithm::Kruskal           => &Kruskal,
+            Algorithm::Wilson            => &Wilson,
+            Algorithm::Eller             => &Eller,
+            Algorithm::HuntAndKill       => &HuntAndKill,
+            Algorithm::Sidewinder        => &Sidewinder,
+            Algorithm::RecursiveDivision => &RecursiveDivision,
+            Algorithm::Dungeon           => &Dungeon,
+            Algorithm::Cave              => &Cave,
+        }
+    }
+}
+
+//文字列からアルゴリズムを得る（コマンドライン引数用）
+impl FromStr for Algorithm
+{   type Err = String;
+    fn from_str( s: &str ) -> Result<Self, Self::Err>
+    {   match s.to_ascii_lowercase().as_str()
+        {   "digger"             => Ok ( Algorithm::Digger            ),
+            "prim"               => Ok ( Algorithm::Prim              ),
+            "kruskal"            => Ok ( Algorithm::Kruskal           ),
+            "wilson"             => Ok ( Algorithm::Wilson            ),
+            "eller"              => Ok ( Algorithm::Eller             ),
+            "hunt-and-kill"      => Ok ( Algorithm::HuntAndKill       ),
+            "sidewinder"         => Ok ( Algorithm::Sidewinder        ),
+            "recursive-division" => Ok ( Algorithm::RecursiveDivision ),
+            "dungeon"            => Ok ( Algorithm::Dungeon           ),
+            "cave"               => Ok ( Algorithm::Cave              ),
+            _ => Err ( format!( "Unknown maze algorithm \"{s}\"" ) ),
+        }
+    }
+}
+
+//生成器の型
+pub struct Digger;            //穴掘り法（壁を掘り進み、行き止まりで後戻りする）
+pub struct Prim;              //プリム法
+pub struct Kruskal;           //クラスカル法
+pub struct Wilson;            //ウィルソン法（ループ消去ランダムウォーク）
+pub struct Eller;             //エラー法（一行ずつ作る）
+pub struct HuntAndKill;       //ハント＆キル法
+pub struct Sidewinder;        //サイドワインダー法
+pub struct RecursiveDivision; //再帰分割法（空地に壁を立てていく）
+pub struct Dungeon;           //ダンジョン（部屋と通路。完全迷路ではない）
+pub struct Cave;              //洞窟（セル・オートマトン。完全迷路ではない）
+
+////////////////////////////////////////////////////////////////////////////////
+
+//区画迷路用のメソッド
+//（外壁の内側で、xとyが共に奇数のマスを区画とし、区画の間のマスを通路か壁にする）
+impl Map
+{   //区画のX座標とY座標の一覧
+    fn node_axes( &self ) -> ( Vec<i32>, Vec<i32> )
+    {   let xs = self.x_range_inner().step_by( 2 ).collect();
+        let ys = self.y_range_inner().step_by( 2 ).collect();
+        ( xs, ys )
+    }
+
+    //区画の一覧（行優先）
+    fn nodes( &self ) -> Vec<IVec2>
+    {   let ( xs, ys ) = self.node_axes();
+        ys.iter().flat_map( | &y | xs.iter().map( move | &x | IVec2::new( x, y ) ) ).collect()
+    }
+
+    //指定の位置が区画か判定する
+    fn is_node( &self, cell: IVec2 ) -> bool
+    {   self.x_range_inner().contains( &cell.x ) &&
+        self.y_range_inner().contains( &cell.y ) &&
+        cell.x % 2 == 1 && cell.y % 2 == 1
+    }
+
+    //四方の隣の区画がある方角のVecを返す
+    fn node_sides( &self, cell: IVec2 ) -> Vec< News >
+    {   NEWS.into_iter().filter( | &news | self.is_node( cell + news + news ) ).collect()
+    }
+
+    //区画から指定の方角の隣の区画まで掘る
+    fn dig_to( &mut self, cell: IVec2, news: News )
+    {   self.set_space( cell + news );
+        self.set_space( cell + news + news );
+    }
+
+    //Vecからランダムに一つ選ぶ
+    fn pick<T: Copy>( &mut self, list: &[ T ] ) -> T
+    {   list[ self.rng.gen_range( 0..list.len() ) ]
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//穴掘り法
+impl MazeGenerator for Digger
+{   fn build( &self, map: &mut Map )
+    {   map.build_labyrinth();
+    }
+}
+
+//プリム法
+impl MazeGenerator for Prim
+{   fn build( &self, map: &mut Map )
+    {   //startの隣の区画を候補にする
+        let start = map.start;
+        let mut frontiers: Vec<IVec2> =
+            map.node_sides( start ).into_iter().map( | news | start + news + news ).collect();
+
+        //候補がなくなるまで、ランダムに選んだ候補を迷路につなげる
+        while ! frontiers.is_empty()
+        {   let cell = frontiers.swap_remove( map.rng.gen_range( 0..frontiers.len() ) );
+            if map.is_space( cell ) { continue } //重複して候補になっていた
+
+            //掘り済みの隣の区画のどれかとつなぐ
+            let sides: Vec<News> = map.node_sides( cell ).into_iter()
+                .filter( | &news | map.is_space( cell + news + news ) ).collect();
+            let news = map.pick( &sides );
+            map.set_space( cell );
+            map.dig_to( cell, news );
+
+            //未掘の隣の区画を候補に加える
+            for news in map.node_sides( cell )
+            {   let next = cell + news + news;
+                if map.is_wall( next ) { frontiers.push( next ) }
+            }
+        }
+    }
+}
+
+//クラスカル法
+impl MazeGenerator for Kruskal
+{   fn build( &self, map: &mut Map )
+    {   //全区画を空地にし、区画ごとに別の集合にする
+        let nodes = map.nodes();
+        nodes.iter().for_each( | &cell | map.set_space( cell ) );
+        let index: HashMap<IVec2, usize> =
+            nodes.iter().enumerate().map( | ( i, &cell ) | ( cell, i ) ).collect();
+        let mut parents: Vec<usize> = ( 0..nodes.len() ).collect();
+
+        //集合の代表を探す
+        fn root( parents: &mut [ usize ], mut i: usize ) -> usize
+        {   while parents[ i ] != i
+            {   parents[ i ] = parents[ parents[ i ] ]; //経路を縮める
+                i = parents[ i ];
+            }
+            i
+        }
+
+        //区画の間の壁をランダムな順に並べる
+        let mut edges = Vec::new();
+        for &cell in nodes.iter()
+        {   for news in [ News::East, News::South ]
+            {   if map.is_node( cell + news + news ) { edges.push( ( cell, news ) ) }
+            }
+        }
+        edges.shuffle( &mut map.rng );
+
+        //別の集合に属する区画の間の壁だけを掘る
+        for ( cell, news ) in edges
+        {   let a = root( &mut parents, index[ &cell ] );
+            let b = root( &mut parents, index[ &( cell + news + news ) ] );
+            if a == b { continue }
+
+            parents[ b ] = a;
+            map.dig_to( cell, news );
+        }
+    }
+}
+
+//ウィルソン法
+impl MazeGenerator for Wilson
+{   fn build( &self, map: &mut Map )
+    {   let mut nodes = map.nodes();
+        nodes.shuffle( &mut map.rng );
+
+        //未掘の区画からランダムウォークし、掘り済みの区画に出会うまで進む
+        let mut walk: HashMap<IVec2, News> = HashMap::new();
+        for origin in nodes
+        {   if map.is_space( origin ) { continue }
+
+            walk.clear();
+            let mut cell = origin;
+            while ! map.is_space( cell )
+            {   let news = map.pick( &map.node_sides( cell ) );
+                walk.insert( cell, news ); //最後に出た方角で上書きされ、ループが消える
+                cell = cell + news + news;
+            }
+
+            //ループを消去した経路を掘る（掘り済みの区画はwalkに記録されていない）
+            let mut cell = origin;
+            while let Some ( &news ) = walk.get( &cell )
+            {   map.set_space( cell );
+                map.dig_to( cell, news );
+                cell = cell + news + news;
+            }
+        }
+    }
+}
+
+//エラー法
+impl MazeGenerator for Eller
+{   fn build( &self, map: &mut Map )
+    {   let ( xs, ys ) = map.node_axes();
+        let mut sets: Vec<usize> = ( 0..xs.len() ).collect();
+        let mut next_id = xs.len();
+
+        for ( j, &y ) in ys.iter().enumerate()
+        {   let is_last = j + 1 == ys.len();
+            xs.iter().for_each( | &x | map.set_space( IVec2::new( x, y ) ) );
+
+            //左右の別の集合をランダムにつなぐ（最終行は全部つなぐ）
+            for i in 0..xs.len() - 1
+            {   if sets[ i ] == sets[ i + 1 ] { continue }
+                if ! is_last && map.rng.gen_bool( 0.5 ) { continue }
+
+                map.dig_to( IVec2::new( xs[ i ], y ), News::East );
+                let ( old, new ) = ( sets[ i + 1 ], sets[ i ] );
+                sets.iter_mut().filter( | id | **id == old ).for_each( | id | *id = new );
+            }
+            if is_last { break }
+
+            //集合ごとに最低一つは下の行へつなぐ
+            let mut next_sets = vec![ usize::MAX; xs.len() ];
+            let mut ids = sets.clone();
+            ids.sort_unstable();
+            ids.dedup();
+            for id in ids
+            {   let mut columns: Vec<usize> =
+                    ( 0..xs.len() ).filter( | &i | sets[ i ] == id ).collect();
+                columns.shuffle( &mut map.rng );
+                let count = map.rng.gen_range( 1..=columns.len() );
+                for &i in columns.iter().take( count )
+                {   map.dig_to( IVec2::new( xs[ i ], y ), News::South );
+                    next_sets[ i ] = id;
+                }
+            }
+
+            //つながらなかった区画は新しい集合にする
+            for id in next_sets.iter_mut().filter( | id | **id == usize::MAX )
+            {   *id = next_id;
+                next_id += 1;
+            }
+            sets = next_sets;
+        }
+    }
+}
+
+//ハント＆キル法
+impl MazeGenerator for HuntAndKill
+{   fn build( &self, map: &mut Map )
+    {   let nodes = map.nodes();
+        let mut scan_from = 0;
+        let mut cell = map.start;
+
+        loop
+        {   //未掘の隣の区画へランダムに進む
+            let sides: Vec<News> = map.node_sides( cell ).into_iter()
+                .filter( | &news | map.is_wall( cell + news + news ) ).collect();
+            if ! sides.is_empty()
+            {   let news = map.pick( &sides );
+                map.dig_to( cell, news );
+                cell = cell + news + news;
+                continue;
+            }
+
+            //行き詰まったら、掘り済みの区画に隣接する未掘の区画を端から探す
+            while scan_from < nodes.len() && map.is_space( nodes[ scan_from ] ) { scan_from += 1 }
+            let found = nodes[ scan_from.. ].iter().find_map
+            (   | &node |
+                {   if map.is_space( node ) { return None }
+                    let sides: Vec<News> = map.node_sides( node ).into_iter()
+                        .filter( | &news | map.is_space( node + news + news ) ).collect();
+                    ( ! sides.is_empty() ).then_some( ( node, sides ) )
+                }
+            );
+
+            //見つからなければ迷路完成
+            let Some ( ( node, sides ) ) = found else { break };
+            let news = map.pick( &sides );
+            map.set_space( node );
+            map.dig_to( node, news );
+            cell = node;
+        }
+    }
+}
+
+//サイドワインダー法
+impl MazeGenerator for Sidewinder
+{   fn build( &self, map: &mut Map )
+    {   let ( xs, ys ) = map.node_axes();
+        let mut run = Vec::new();
+
+        for ( j, &y ) in ys.iter().enumerate()
+        {   run.clear();
+            for ( i, &x ) in xs.iter().enumerate()
+            {   let cell = IVec2::new( x, y );
+                map.set_space( cell );
+                run.push( cell );
+
+                //東端か、ランダムに一続きの区画を閉じる（最上行は閉じない）
+                let is_east_end = i + 1 == xs.len();
+                let is_top = j == 0;
+                if is_east_end || ( ! is_top && map.rng.gen_bool( 0.5 ) )
+                {   //一続きの区画のどれかから北へつなぐ
+                    if ! is_top
+                    {   let node = map.pick( &run );
+                        map.dig_to( node, News::North );
+                    }
+                    run.clear();
+                }
+                else
+                {   map.dig_to( cell, News::East );
+                }
+            }
+        }
+    }
+}
+
+//再帰分割法
+impl MazeGenerator for RecursiveDivision
+{   fn build( &self, map: &mut Map )
+    {   let ( xs, ys ) = map.node_axes();
+
+        //区画全体を空地にする
+        for x in xs[ 0 ]..=xs[ xs.len() - 1 ]
+        {   for y in ys[ 0 ]..=ys[ ys.len() - 1 ]
+            {   map.set_space( IVec2::new( x, y ) );
+            }
+        }
+
+        //区画の範囲[i0,i1)×[j0,j1)を分割する
+        let mut stack = vec![ ( 0, xs.len(), 0, ys.len() ) ];
+        while let Some ( ( i0, i1, j0, j1 ) ) = stack.pop()
+        {   let ( w, h ) = ( i1 - i0, j1 - j0 );
+            if w < 2 && h < 2 { continue }
+
+            //長い辺を分割する（同じならランダム）
+            let is_horizontal =
+                if w < h { true } else if h < w { false } else { map.rng.gen_bool( 0.5 ) };
+
+            if is_horizontal
+            {   //k-1行目とk行目の間に壁を立て、一か所だけ通路を残す
+                let k = map.rng.gen_range( j0 + 1..j1 );
+                let gap = xs[ map.rng.gen_range( i0..i1 ) ];
+                let y = ys[ k ] - 1;
+                for x in xs[ i0 ]..=xs[ i1 - 1 ]
+                {   if x != gap { map.set_wall( IVec2::new( x, y ) ) }
+                }
+                stack.push( ( i0, i1, j0, k ) );
+                stack.push( ( i0, i1, k, j1 ) );
+            }
+            else
+            {   //k-1列目とk列目の間に壁を立て、一か所だけ通路を残す
+                let k = map.rng.gen_range( i0 + 1..i1 );
+                let gap = ys[ map.rng.gen_range( j0..j1 ) ];
+                let x = xs[ k ] - 1;
+                for y in ys[ j0 ]..=ys[ j1 - 1 ]
+                {   if y != gap { map.set_wall( IVec2::new( x, y ) ) }
+                }
+                stack.push( ( i0, k, j0, j1 ) );
+                stack.push( ( k, i1, j0, j1 ) );
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
\ No newline at end of file
@@ -0,0 +1,105 @@
+use super::*;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//洞窟の設定
+const CAVE_WALL_RATIO: f64 = 0.45;   //最初にばらまく壁の割合
+const CAVE_SMOOTHING: usize = 7;     //なめらかにする回数
+const CAVE_PILLARS: usize = 4;       //そのうち、広間に柱を立てる回数（最初から数える）
+const CAVE_BIRTH: usize = 5;         //周囲8マスの壁がこれ以上なら空地が壁になる
+const CAVE_SURVIVAL: usize = 4;      //周囲8マスの壁がこれ以上なら壁は壁のまま
+const CAVE_LONELY: usize = 2;        //周囲24マスの壁がこれ以下なら柱を立てる
+const CAVE_MIN_RATIO: f32 = 0.3;     //startの洞窟がこの割合より狭ければ作り直す
+const CAVE_RETRIES: usize = 8;       //作り直す回数の上限
+
+////////////////////////////////////////////////////////////////////////////////
+
+//洞窟（セル・オートマトン。startにつながる洞窟だけ残す）
+impl MazeGenerator for Cave
+{   fn build( &self, map: &mut Map )
+    {   let inner = ( map.width - 2 ) as usize * ( map.height - 2 ) as usize;
+        for _ in 0..CAVE_RETRIES
+        {   map.scatter_walls();
+            for i in 0..CAVE_SMOOTHING { map.smooth_cave( i < CAVE_PILLARS ) }
+
+            //startの洞窟が十分広ければ完成
+            let cave = map.fill_unreachable();
+            if cave as f32 >= inner as f32 * CAVE_MIN_RATIO { break }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//洞窟用のメソッド
+impl Map
+{   //外壁の内側に壁をランダムにばらまく（start周辺は空地にする）
+    fn scatter_walls( &mut self )
+    {   for x in self.x_range_inner()
+        {   for y in self.y_range_inner()
+            {   let cell = IVec2::new( x, y );
+                if self.rng.gen_bool( CAVE_WALL_RATIO ) { self.set_wall( cell ) } else { self.set_space( cell ) }
+            }
+        }
+        self.clear_around_start();
+    }
+
+    //周囲8マスの壁の数でなめらかにする（範囲外は壁として数える）
+    //柱を立てるなら、周囲24マスに壁がほとんどない空地も壁にして、広間が広がりすぎないようにする
+    fn smooth_cave( &mut self, is_pillar: bool )
+    {   let mut walls = Vec::new();
+        for x in self.x_range_inner()
+        {   for y in self.y_range_inner()
+            {   let cell = IVec2::new( x, y );
+                let count = AROUND.iter().filter( | &&d | self.is_wall( cell + d ) ).count();
+                let limit = if self.is_wall( cell ) { CAVE_SURVIVAL } else { CAVE_BIRTH };
+                let is_lonely = is_pillar && self.count_walls_within( cell, 2 ) <= CAVE_LONELY;
+                walls.push( ( cell, count >= limit || is_lonely ) );
+            }
+        }
+
+        for ( cell, is_wall ) in walls
+        {   if is_wall { self.set_wall( cell ) } else { self.set_space( cell ) }
+        }
+        self.clear_around_start();
+    }
+
+    //cellを中心にした(2r+1)×(2r+1)マスの壁の数（cell自身を除く）
+    fn count_walls_within( &self, cell: IVec2, r: i32 ) -> usize
+    {   ( -r..=r ).flat_map( | dx | ( -r..=r ).map( move | dy | IVec2::new( dx, dy ) ) )
+            .filter( | &d | d != IVec2::ZERO && self.is_wall( cell + d ) )
+            .count()
+    }
+
+    //startとその周囲8マスを空地にする（外壁は除く）
+    fn clear_around_start( &mut self )
+    {   let start = self.start;
+        for cell in AROUND.iter().map( | &d | start + d ).chain( [ start ] )
+        {   if self.x_range_inner().contains( &cell.x ) && self.y_range_inner().contains( &cell.y )
+            {   self.set_space( cell );
+            }
+        }
+    }
+
+    //startからたどり着けない空地を壁で埋め、残った空地の数を返す
+    fn fill_unreachable( &mut self ) -> usize
+    {   let mut visited = HashSet::from( [ self.start ] );
+        let mut queue = VecDeque::from( [ self.start ] );
+        while let Some ( cell ) = queue.pop_front()
+        {   for news in self.get_sides_space( cell )
+            {   if visited.insert( cell + news ) { queue.push_back( cell + news ) }
+            }
+        }
+
+        let isolated: Vec<_> = self.cells_where( | c | c.terrain == Terrain::Space )
+            .filter( | cell | ! visited.contains( cell ) )
+            .collect();
+        isolated.into_iter().for_each( | cell | self.set_wall( cell ) );
+
+        visited.len()
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
@@ -0,0 +1,112 @@
+use super::*;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//ダンジョンの設定（部屋の大きさは区画の数。n区画は2n-1マス）
+const ROOM_NODES_MIN: usize = 2;
+const ROOM_NODES_MAX: usize = 4;
+const ROOM_TRIES_PER_NODES: usize = 2; //この区画数ごとに部屋の配置を一回試す
+
+//部屋（区画の添字の範囲[i0,i1)×[j0,j1)）
+#[derive( Clone, Copy )]
+struct Room { i0: usize, i1: usize, j0: usize, j1: usize }
+
+impl Room
+{   //区画一つ分の隙間を空けずに重なるか
+    fn is_too_close( &self, other: &Room ) -> bool
+    {   self.i0 <= other.i1 && other.i0 <= self.i1 &&
+        self.j0 <= other.j1 && other.j0 <= self.j1
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//ダンジョン（大小の部屋を通路でつなぐ。部屋のマスには部屋番号を付ける）
+impl MazeGenerator for Dungeon
+{   fn build( &self, map: &mut Map )
+    {   let ( xs, ys ) = map.node_axes();
+
+        //最初の部屋はstartを含むように置く
+        let si = xs.iter().position( | &x | x == map.start.x ).unwrap_or( 0 );
+        let sj = ys.iter().position( | &y | y == map.start.y ).unwrap_or( 0 );
+        let ( w, h ) = ( map.room_nodes( xs.len() ), map.room_nodes( ys.len() ) );
+        let i0 = map.rng.gen_range( ( si + 1 ).saturating_sub( w )..=si ).min( xs.len() - w );
+        let j0 = map.rng.gen_range( ( sj + 1 ).saturating_sub( h )..=sj ).min( ys.len() - h );
+        let mut rooms = vec![ Room { i0, i1: i0 + w, j0, j1: j0 + h } ];
+
+        //他の部屋と離れていれば置く
+        let tries = xs.len() * ys.len() / ROOM_TRIES_PER_NODES;
+        for _ in 0..tries
+        {   if rooms.len() >= ROOM_MAX as usize { break }
+
+            let ( w, h ) = ( map.room_nodes( xs.len() ), map.room_nodes( ys.len() ) );
+            let i0 = map.rng.gen_range( 0..=xs.len() - w );
+            let j0 = map.rng.gen_range( 0..=ys.len() - h );
+            let room = Room { i0, i1: i0 + w, j0, j1: j0 + h };
+            if rooms.iter().any( | other | room.is_too_close( other ) ) { continue }
+
+            rooms.push( room );
+        }
+
+        //部屋を掘って部屋番号を付ける
+        for ( id, room ) in rooms.iter().enumerate()
+        {   for x in xs[ room.i0 ]..=xs[ room.i1 - 1 ]
+            {   for y in ys[ room.j0 ]..=ys[ room.j1 - 1 ]
+                {   let cell = IVec2::new( x, y );
+                    map.set_space( cell );
+                    map.set_room( cell, id as u16 );
+                }
+            }
+        }
+
+        //部屋の中の区画を一つずつ選び、通路の出入口にする
+        let doors: Vec<IVec2> = rooms.iter().map
+        (   | room |
+            IVec2::new
+            (   xs[ map.rng.gen_range( room.i0..room.i1 ) ],
+                ys[ map.rng.gen_range( room.j0..room.j1 ) ],
+            )
+        ).collect();
+
+        //置いた順に、先に置いた部屋のうち一番近いものと通路でつなぐ（全体が木になる）
+        for k in 1..doors.len()
+        {   let from = doors[ k ];
+            let Some ( &to ) = doors[ ..k ].iter()
+                .min_by_key( | &&to | { let d = ( to - from ).abs(); d.x + d.y } ) else { continue };
+            map.dig_corridor( from, to );
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//ダンジョン用のメソッド
+impl Map
+{   //部屋の一辺の区画数をランダムに決める（区画が少なければ縮める）
+    fn room_nodes( &mut self, nodes: usize ) -> usize
+    {   let max = ROOM_NODES_MAX.min( nodes );
+        let min = ROOM_NODES_MIN.min( max );
+        self.rng.gen_range( min..=max )
+    }
+
+    //二つの区画の間をL字の通路で掘る（部屋の中は掘らないので部屋番号は残る）
+    fn dig_corridor( &mut self, from: IVec2, to: IVec2 )
+    {   //縦横どちらを先に進むかはランダム
+        let corner =
+            if self.rng.gen_bool( 0.5 ) { IVec2::new( to.x, from.y ) } else { IVec2::new( from.x, to.y ) };
+
+        for ( a, b ) in [ ( from, corner ), ( corner, to ) ]
+        {   let step = ( b - a ).signum();
+            let mut cell = a;
+            loop
+            {   if self.is_wall( cell ) { self.set_space( cell ) }
+                if cell == b { break }
+                cell += step;
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
@@ -0,0 +1,127 @@
+use super::*;
+
+//standard library
+use std::path::Path;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//マスクのテキスト形式（load_assets.rsのNOWLOADINGと同じく、空白と'.'以外の文字が形の内側）
+//"//"で始まる行は読み飛ばす（空行は形の一部として残す）
+const MASK_OUTSIDE: [ char; 2 ] = [ ' ', '.' ];
+const MASK_COMMENT: &str = "//";
+
+//PNGでは、不透明で暗いピクセルが形の内側
+const MASK_PNG_ALPHA: u8 = 128;
+const MASK_PNG_LUMA : u32 = 128;
+
+//迷路を作る範囲の形（マップの外壁の内側に引き伸ばして使う）
+#[derive( Clone, PartialEq, Eq, Debug )]
+pub struct MazeMask
+{   width : usize,
+    height: usize,
+    pixels: Vec<bool>, //行優先
+}
+
+impl MazeMask
+{   //行の配列から作る（行の長さは揃っていなくてよい）
+    pub fn from_rows( rows: &[ &str ] ) -> Result<Self, String>
+    {   //末尾の空行は形に含めない
+        let rows = &rows[ ..rows.iter().rposition( | row | ! row.trim().is_empty() ).map_or( 0, | i | i + 1 ) ];
+        let width = rows.iter().map( | row | row.chars().count() ).max().unwrap_or( 0 );
+        let height = rows.len();
+
+        let mut pixels = vec![ false; width * height ];
+        for ( y, row ) in rows.iter().enumerate()
+        {   for ( x, char ) in row.chars().enumerate()
+            {   pixels[ y * width + x ] = ! MASK_OUTSIDE.contains( &char );
+            }
+        }
+
+        Self::new( width, height, pixels )
+    }
+
+    //テキストから作る
+    pub fn from_ascii( text: &str ) -> Result<Self, String>
+    {   let rows: Vec<&str> = text.lines()
+            .filter( | line | ! line.starts_with( MASK_COMMENT ) )
+            .collect();
+        Self::from_rows( &rows )
+    }
+
+    //PNGから作る
+    pub fn from_png( bytes: &[ u8 ] ) -> Result<Self, String>
+    {   let image = image::load_from_memory_with_format( bytes, image::ImageFormat::Png )
+            .map_err( | err | err.to_string() )?
+            .to_rgba8();
+
+        let ( width, height ) = ( image.width() as usize, image.height() as usize );
+        let pixels = image.pixels()
+            .map
+            (   | pixel |
+                {   let [ r, g, b, a ] = pixel.0;
+                    a >= MASK_PNG_ALPHA && ( r as u32 + g as u32 + b as u32 ) / 3 < MASK_PNG_LUMA
+                }
+            )
+            .collect();
+
+        Self::new( width, height, pixels )
+    }
+
+    //ファイルから作る（拡張子が.pngならPNG、それ以外はテキスト）
+    pub fn load( path: &str ) -> Result<Self, String>
+    {   let error = | err: std::io::Error | format!( "{path}: {err}" );
+        let is_png = Path::new( path ).extension().is_some_and( | ext | ext.eq_ignore_ascii_case( "png" ) );
+
+        let mask = if is_png
+        {   Self::from_png( &std::fs::read( path ).map_err( error )? )
+        }
+        else
+        {   Self::from_ascii( &std::fs::read_to_string( path ).map_err( error )? )
+        };
+        mask.map_err( | err | format!( "{path}: {err}" ) )
+    }
+
+    fn new( width: usize, height: usize, pixels: Vec<bool> ) -> Result<Self, String>
+    {   if ! pixels.contains( &true ) { return Err ( "mask has no inside pixel".to_string() ) }
+        Ok ( Self { width, height, pixels } )
+    }
+
+    //マップの外壁の内側に引き伸ばしたとき、指定のマスが形の内側か
+    fn contains( &self, cell: IVec2, inner: IVec2 ) -> bool
+    {   let x = ( cell.x - 1 ) as usize * self.width  / inner.x as usize;
+        let y = ( cell.y - 1 ) as usize * self.height / inner.y as usize;
+        self.pixels[ y * self.width + x ]
+    }
+}
+
+//マスクを得る
+//コマンドライン引数か設定ファイルでファイルを指定できる（例：--mask masks/nowloading.txt）
+pub fn mask_setting() -> Option<MazeMask>
+{   let path = misc::setting( "mask" )?;
+    MazeMask::load( &path ).map_err( | err | warn!( "Invalid maze mask {err}" ) ).ok()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//Mapのpubメソッド（マスク）
+impl Map
+{   //指定のマスが迷路を作れる範囲か（マスクがなければ外壁の内側全部）
+    pub fn is_in_mask( &self, cell: IVec2 ) -> bool
+    {   if ! self.x_range_inner().contains( &cell.x ) || ! self.y_range_inner().contains( &cell.y ) { return false }
+        let inner = IVec2::new( self.width - 2, self.height - 2 );
+        self.mask.as_ref().is_none_or( | mask | mask.contains( cell, inner ) )
+    }
+
+    //マスクの内側で、マップの中央に一番近いマス
+    pub fn masked_start( &self ) -> Option<IVec2>
+    {   let center = IVec2::new( self.width / 2, self.height / 2 );
+        self.x_range_inner()
+            .flat_map( | x | self.y_range_inner().map( move | y | IVec2::new( x, y ) ) )
+            .filter( | &cell | self.is_in_mask( cell ) )
+            .min_by_key( | &cell | ( cell - center ).length_squared() )
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
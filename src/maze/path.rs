@@ -0,0 +1,105 @@
+use super::*;
+
+//standard library
+use std::collections::{ BinaryHeap, HashMap };
+use std::cmp::Reverse;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//Mapのpubメソッド（経路探索）
+//経路はfromとtoを含むマスのVec。fromかtoが空地でない場合や、たどり着けない場合はNone
+impl Map
+{   //幅優先探索で最短経路を探す
+    pub fn shortest_path_bfs( &self, from: IVec2, to: IVec2 ) -> Option<Vec<IVec2>>
+    {   self.shortest_path_bfs_on( self.floor, from, to )
+    }
+
+    //指定の階で、幅優先探索で最短経路を探す
+    pub fn shortest_path_bfs_on( &self, floor: usize, from: IVec2, to: IVec2 ) -> Option<Vec<IVec2>>
+    {   if ! self.is_space_on( floor, from ) || ! self.is_space_on( floor, to ) { return None }
+
+        let mut came_from = HashMap::from( [ ( from, from ) ] );
+        let mut queue = VecDeque::from( [ from ] );
+        while let Some ( cell ) = queue.pop_front()
+        {   if cell == to { return Some ( trace_back( &came_from, to ) ) }
+
+            for news in self.get_sides_space_on( floor, cell )
+            {   let next = cell + news;
+                if came_from.contains_key( &next ) { continue }
+                came_from.insert( next, cell );
+                queue.push_back( next );
+            }
+        }
+
+        None //たどり着けない
+    }
+
+    //A*で最短経路を探す（ヒューリスティックはマンハッタン距離）
+    pub fn shortest_path_astar( &self, from: IVec2, to: IVec2 ) -> Option<Vec<IVec2>>
+    {   if ! self.is_space( from ) || ! self.is_space( to ) { return None }
+
+        let heuristic = | cell: IVec2 | { let d = ( to - cell ).abs(); d.x + d.y };
+        let mut came_from = HashMap::from( [ ( from, from ) ] );
+        let mut costs = HashMap::from( [ ( from, 0 ) ] );
+
+        //推定コストの小さい順に取り出す（IVec2はOrdでないのでタプルにする）
+        let mut open = BinaryHeap::from( [ Reverse ( ( heuristic( from ), 0, from.x, from.y ) ) ] );
+        while let Some ( Reverse ( ( _, cost, x, y ) ) ) = open.pop()
+        {   let cell = IVec2::new( x, y );
+            if cell == to { return Some ( trace_back( &came_from, to ) ) }
+            if cost > costs[ &cell ] { continue } //より安い経路で処理済み
+
+            for news in self.get_sides_space( cell )
+            {   let next = cell + news;
+                let next_cost = cost + 1;
+                if costs.get( &next ).is_some_and( | &c | c <= next_cost ) { continue }
+
+                costs.insert( next, next_cost );
+                came_from.insert( next, cell );
+                open.push( Reverse ( ( next_cost + heuristic( next ), next_cost, next.x, next.y ) ) );
+            }
+        }
+
+        None //たどり着けない
+    }
+
+    //指定の位置から道なりに最も遠いマスを返す（幅優先探索）
+    pub fn farthest_cell( &self, from: IVec2 ) -> IVec2
+    {   let mut visited = HashSet::from( [ from ] );
+        let mut queue = VecDeque::from( [ from ] );
+        let mut farthest = from;
+        while let Some ( cell ) = queue.pop_front()
+        {   farthest = cell; //最後に取り出したマスが最も遠い
+            for news in self.get_sides_space( cell )
+            {   let next = cell + news;
+                if visited.insert( next ) { queue.push_back( next ) }
+            }
+        }
+
+        farthest
+    }
+}
+
+//経路(マスのVec)を一歩ずつの方角のVecにする
+pub fn path_to_news( path: &[ IVec2 ] ) -> Vec<News>
+{   path.windows( 2 )
+    .filter_map( | step | NEWS.into_iter().find( | &news | step[ 0 ] + news == step[ 1 ] ) )
+    .collect()
+}
+
+//探索の記録をゴールから逆にたどって経路を作る
+fn trace_back( came_from: &HashMap<IVec2, IVec2>, to: IVec2 ) -> Vec<IVec2>
+{   let mut path = vec![ to ];
+    let mut cell = to;
+    while came_from[ &cell ] != cell
+    {   cell = came_from[ &cell ];
+        path.push( cell );
+    }
+    path.reverse();
+
+    path
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
\ No newline at end of file
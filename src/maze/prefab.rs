@@ -0,0 +1,263 @@
+use super::*;
+
+//standard library
+use std::collections::HashMap;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//プレハブのテキスト形式（迷路のテキスト形式と同じ文字。'?'は穴掘り法に任せるマス）
+//空行で区切って複数並べられる。"//"で始まる行は読み飛ばす
+//  'S' があればそこがスタート、'G' があればそこがゴールになる
+
+//プレハブを置く位置を探す回数
+const PREFAB_TRIES: usize = 200;
+
+//プレハブを迷路につなげなかったときに、階を作り直す回数
+pub const PREFAB_RETRIES: usize = 8;
+
+//迷路に埋め込む固定の区画（広間、スタートの部屋、ゴールの部屋など）
+#[derive( Clone, PartialEq, Eq, Debug )]
+pub struct Prefab
+{   size : IVec2,                      //縦横幅
+    cells: Vec<( IVec2, Terrain )>,    //区画のマス（'?'以外。左上からの相対位置）
+    start: Option<IVec2>,              //スタート（相対位置）
+    goal : Option<IVec2>,              //ゴール（相対位置）
+}
+
+impl Prefab
+{   //テキストからプレハブの一覧を作る
+    pub fn from_ascii( text: &str ) -> Result<Vec<Self>, MazeParseError>
+    {   let mut prefabs = Vec::new();
+        let mut block: Vec<( usize, &str )> = Vec::new();
+        for ( i, line ) in text.lines().chain( [ "" ] ).enumerate()
+        {   let line = line.trim_end();
+            if line.starts_with( ASCII_COMMENT ) { continue }
+            if ! line.is_empty() { block.push( ( i + 1, line ) ); continue }
+            if block.is_empty() { continue }
+
+            prefabs.push( Self::from_rows( &block )? );
+            block.clear();
+        }
+
+        Ok ( prefabs )
+    }
+
+    //行番号付きの行から一つ作る
+    fn from_rows( rows: &[ ( usize, &str ) ] ) -> Result<Self, MazeParseError>
+    {   let error = | line, column, message: String | MazeParseError { line, column, message };
+        let first_line = rows[ 0 ].0;
+
+        let mut prefab = Prefab { size: IVec2::ZERO, cells: Vec::new(), start: None, goal: None };
+        for ( y, &( line, row ) ) in rows.iter().enumerate()
+        {   for ( x, char ) in row.chars().enumerate()
+            {   let cell = IVec2::new( x as i32, y as i32 );
+                let column = x + 1;
+                prefab.size = prefab.size.max( cell + IVec2::ONE );
+
+                //スタートとゴールは一つまで
+                let marker = match char
+                {   ASCII_START | ASCII_START_DEADEND => Some ( ( &mut prefab.start, "start" ) ),
+                    ASCII_GOAL  | ASCII_GOAL_DEADEND  => Some ( ( &mut prefab.goal , "goal"  ) ),
+                    _ => None,
+                };
+                if let Some ( ( position, name ) ) = marker
+                {   if position.is_some()
+                    {   return Err ( error( line, column, format!( "duplicate {name} '{char}'" ) ) );
+                    }
+                    *position = Some ( cell );
+                }
+
+                let terrain = match char
+                {   ASCII_UNDEF => continue,
+                    ASCII_WALL  => Terrain::Wall,
+                    ASCII_SPACE | ASCII_SPACE_DEADEND | ASCII_START | ASCII_START_DEADEND
+                    | ASCII_GOAL | ASCII_GOAL_DEADEND => Terrain::Space,
+                    _ => return Err ( error( line, column, format!( "unknown prefab char '{char}'" ) ) ),
+                };
+                prefab.cells.push( ( cell, terrain ) );
+            }
+        }
+
+        //空地は一つ以上あって、全部つながっていること（ドアは一つしか作らないので）
+        let spaces: HashSet<IVec2> = prefab.spaces().collect();
+        let Some ( &origin ) = spaces.iter().next()
+        else { return Err ( error( first_line, 1, "prefab has no open cell".to_string() ) ) };
+        let mut visited = HashSet::from( [ origin ] );
+        let mut queue = VecDeque::from( [ origin ] );
+        while let Some ( cell ) = queue.pop_front()
+        {   for news in NEWS
+            {   let next = cell + news;
+                if spaces.contains( &next ) && visited.insert( next ) { queue.push_back( next ) }
+            }
+        }
+        if visited.len() != spaces.len()
+        {   return Err ( error( first_line, 1, "prefab open cells are not connected".to_string() ) );
+        }
+
+        Ok ( prefab )
+    }
+
+    //空地のマス（相対位置）
+    fn spaces( &self ) -> impl Iterator<Item = IVec2> + '_
+    {   self.cells.iter().filter( | ( _, terrain ) | *terrain == Terrain::Space ).map( | &( cell, _ ) | cell )
+    }
+}
+
+//プレハブの一覧を得る
+//コマンドライン引数か設定ファイルでファイルを指定できる（例：--prefabs assets/prefabs/landmarks.txt）
+pub fn prefabs_setting() -> Vec<Prefab>
+{   let Some ( path ) = misc::setting( "prefabs" ) else { return Vec::new() };
+    load_prefabs( &path ).unwrap_or_else( | err | { warn!( "Invalid prefabs {err}" ); Vec::new() } )
+}
+
+//ファイルからプレハブの一覧を読む
+pub fn load_prefabs( path: &str ) -> Result<Vec<Prefab>, String>
+{   let text = std::fs::read_to_string( path ).map_err( | err | format!( "{path}: {err}" ) )?;
+    Prefab::from_ascii( &text ).map_err( | err | format!( "{path}: {err}" ) )
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//Mapのメソッド（プレハブ）
+impl Map
+{   //プレハブをランダムな位置に置く（スタートを含むものから置く）
+    //置いた区画と周り1マスにPrefabフラグを付け、置いたプレハブごとに空地の一覧を返す
+    //複数階なら、スタートを含むものは一階に、それ以外は最上階に置く（ゴールは最上階でだけ使う）
+    pub fn place_prefabs( &mut self, is_first: bool, is_last: bool ) -> Vec<Vec<IVec2>>
+    {   let mut prefabs: Vec<Prefab> = self.prefabs.iter()
+            .filter( | prefab | if prefab.start.is_some() { is_first } else { is_last } )
+            .cloned()
+            .collect();
+        prefabs.sort_by_key( | prefab | prefab.start.is_none() );
+
+        let mut placed = Vec::new();
+        for prefab in prefabs
+        {   let Some ( zero ) = self.find_prefab_position( &prefab )
+            else { warn!( "No room for a {}x{} prefab", prefab.size.x, prefab.size.y ); continue };
+
+            //周り1マスを先に壁にして、その上に区画を置く
+            for &( cell, _ ) in prefab.cells.iter()
+            {   for d in AROUND
+                {   let around = zero + cell + d;
+                    if self.is_wall( around ) && ! self.has_flag( around, Flag::Prefab )
+                    {   self.set_wall( around );
+                        self.add_flag( around, Flag::Prefab );
+                    }
+                }
+            }
+            for &( cell, terrain ) in prefab.cells.iter()
+            {   let cell = zero + cell;
+                if terrain == Terrain::Space { self.set_space( cell ) } else { self.set_wall( cell ) }
+                self.add_flag( cell, Flag::Prefab );
+            }
+
+            if let Some ( start ) = prefab.start { self.start = zero + start }
+            if let Some ( goal  ) = prefab.goal.filter( | _ | is_last ) { self.goal = Some ( zero + goal ) }
+            placed.push( prefab.spaces().map( | cell | zero + cell ).collect() );
+        }
+
+        placed
+    }
+
+    //プレハブを置ける左上の位置を探す
+    //（区画は外壁とマスクの内側。周り1マスも含めて、他のプレハブとスタートに重ならない）
+    fn find_prefab_position( &mut self, prefab: &Prefab ) -> Option<IVec2>
+    {   let max = IVec2::new( self.width, self.height ) - 1 - prefab.size;
+        if max.x < 1 || max.y < 1 { return None }
+
+        for _ in 0..PREFAB_TRIES
+        {   let zero = IVec2::new( self.rng.gen_range( 1..=max.x ), self.rng.gen_range( 1..=max.y ) );
+            let is_free = prefab.cells.iter().all
+            (   | &( cell, _ ) |
+                {   let cell = zero + cell;
+                    self.is_in_mask( cell )
+                    && AROUND.iter().chain( [ &IVec2::ZERO ] ).all
+                    (   | &d |
+                        ! self.has_flag( cell + d, Flag::Prefab )
+                        && ( prefab.start.is_some() || cell + d != self.start )
+                    )
+                }
+            );
+            if is_free { return Some ( zero ) }
+        }
+
+        None
+    }
+
+    //穴掘り法を始めるマス（スタートがプレハブの中なら、プレハブの外の掘れるマス）
+    pub fn labyrinth_origin( &mut self ) -> IVec2
+    {   if ! self.has_flag( self.start, Flag::Prefab ) { return self.start }
+
+        let candidates: Vec<IVec2> = self.cells_where( | c | c.terrain == Terrain::Wall && ! c.flags.contains( Flag::Prefab ) )
+            .filter( | &cell | self.is_in_mask( cell ) && AROUND.iter().all( | &d | self.is_wall( cell + d ) ) )
+            .collect();
+        if candidates.is_empty() { return self.start }
+
+        let origin = candidates[ self.rng.gen_range( 0..candidates.len() ) ];
+        self.set_space( origin );
+        origin
+    }
+
+    //迷路につながっていないプレハブを、壁を一番少なく掘る通路で迷路につなぐ
+    //（迷路がなければ、最初のプレハブに他のプレハブをつなぐ）
+    //つなげないプレハブがあればfalseを返す
+    pub fn connect_prefabs( &mut self, placed: &[ Vec<IVec2> ] ) -> bool
+    {   let Some ( origin ) = self.cells_where( | c | c.terrain == Terrain::Space && ! c.flags.contains( Flag::Prefab ) )
+            .next()
+            .or( placed.first().and_then( | spaces | spaces.first().copied() ) )
+        else { return true };
+
+        for spaces in placed
+        {   let connected = self.reachable_from( origin );
+            if spaces.iter().any( | cell | connected.contains( cell ) ) { continue }
+
+            //プレハブの空地から壁だけを通る幅優先探索で、つながっている空地を探す
+            let mut parents: HashMap<IVec2, IVec2> = HashMap::new();
+            let mut frontier: Vec<IVec2> = spaces.clone();
+            let mut exits = Vec::new();
+            while exits.is_empty() && ! frontier.is_empty()
+            {   let mut next_frontier = Vec::new();
+                for &cell in frontier.iter()
+                {   for news in NEWS
+                    {   let next = cell + news;
+                        if parents.contains_key( &next ) || spaces.contains( &next ) { continue }
+                        if connected.contains( &next )
+                        {   exits.push( cell );
+                        }
+                        else if self.is_wall( next ) && self.is_in_mask( next )
+                        {   parents.insert( next, cell );
+                            next_frontier.push( next );
+                        }
+                    }
+                }
+                frontier = next_frontier;
+            }
+
+            //一番近いもののどれかを選んで掘る
+            if exits.is_empty() { return false }
+            let mut cell = exits[ self.rng.gen_range( 0..exits.len() ) ];
+            while let Some ( &parent ) = parents.get( &cell )
+            {   self.set_space( cell );
+                cell = parent;
+            }
+        }
+
+        true
+    }
+
+    //指定のマスからたどり着ける空地
+    fn reachable_from( &self, origin: IVec2 ) -> HashSet<IVec2>
+    {   let mut visited = HashSet::from( [ origin ] );
+        let mut queue = VecDeque::from( [ origin ] );
+        while let Some ( cell ) = queue.pop_front()
+        {   for news in self.get_sides_space( cell )
+            {   if visited.insert( cell + news ) { queue.push_back( cell + news ) }
+            }
+        }
+        visited
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
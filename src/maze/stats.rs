@@ -0,0 +1,123 @@
+use super::*;
+
+//standard library
+use std::fmt;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//迷路の分析結果
+#[derive( Clone, Copy, PartialEq, Debug, Default )]
+pub struct MazeStats
+{   pub open_cells     : usize, //空地の数
+    pub dead_ends      : usize, //行き止り(四方の空地が一つ)の数
+    pub corridors      : usize, //通路(四方の空地が二つ)の数
+    pub junctions      : usize, //分岐(四方の空地が三つ以上)の数
+    pub rooms          : usize, //部屋の数(ダンジョン以外は0)
+    pub solution_length: Option<usize>, //階の入口から出口までの最短歩数(たどり着けなければNone)
+    pub solution_ratio : f32,   //最短経路のマス数÷空地の数
+    pub longest_branch : usize, //最短経路から外れた枝道の最長の歩数
+    pub decision_points: usize, //最短経路上で進む道を選ぶ必要があるマスの数
+    pub difficulty     : f32,   //難易度の点数
+}
+
+//難易度の区分
+#[derive( Clone, Copy, PartialEq, Eq, Debug )]
+pub enum Difficulty { Easy, Normal, Hard }
+
+//難易度の区分の境目(点数)
+//点数は迷路の広さにほぼ比例する。11x11の迷路は5～11点、21x21は15～35点、
+//初期値の51x51では、Primが40点前後、穴掘り法が110～140点になる
+//（小さい迷路はEasy、初期値の広さで枝道の深いものだけHardになるように決めた）
+const DIFFICULTY_NORMAL: f32 = 20.0;
+const DIFFICULTY_HARD  : f32 = 80.0;
+
+impl MazeStats
+{   //難易度の区分を返す
+    pub fn rank( &self ) -> Difficulty
+    {   match self.difficulty
+        {   d if d < DIFFICULTY_NORMAL => Difficulty::Easy,
+            d if d < DIFFICULTY_HARD   => Difficulty::Normal,
+            _ => Difficulty::Hard,
+        }
+    }
+}
+
+impl fmt::Display for MazeStats
+{   fn fmt( &self, f: &mut fmt::Formatter ) -> fmt::Result
+    {   let solution = self.solution_length.map_or( "unreachable".to_string(), | n | n.to_string() );
+        write!
+        (   f,
+            "open {} / dead-ends {} / corridors {} / junctions {} / rooms {} / solution {} ({:.1}%) / \
+             longest branch {} / decisions {} / difficulty {:.1} ({:?})",
+            self.open_cells, self.dead_ends, self.corridors, self.junctions, self.rooms,
+            solution, self.solution_ratio * 100.0,
+            self.longest_branch, self.decision_points, self.difficulty, self.rank(),
+        )
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//Mapのpubメソッド（分析）
+impl Map
+{   //今の階の迷路を分析する
+    pub fn stats( &self ) -> MazeStats
+    {   self.stats_on( self.floor )
+    }
+
+    //指定の階の迷路を分析する（今の階は変えない）
+    pub fn stats_on( &self, floor: usize ) -> MazeStats
+    {   let mut stats = MazeStats::default();
+
+        //空地を四方の空地の数で分類する
+        let mut rooms = HashSet::new();
+        for cell in self.cells_where_on( floor, | c | c.terrain == Terrain::Space )
+        {   stats.open_cells += 1;
+            if let Some ( id ) = self.cell_on( floor, cell ).and_then( | c | c.flags.room() ) { rooms.insert( id ); }
+            match self.get_sides_space_on( floor, cell ).len()
+            {   0 => (),
+                1 => stats.dead_ends += 1,
+                2 => stats.corridors += 1,
+                _ => stats.junctions += 1,
+            }
+        }
+        stats.rooms = rooms.len();
+
+        //最短経路を調べる（複数階なら、その階の入口から出口まで）
+        let entrance = self.floor_entrance_on( floor );
+        let o_path = self.floor_exit_on( floor ).and_then( | exit | self.shortest_path_bfs_on( floor, entrance, exit ) );
+        let Some ( path ) = o_path else { return stats };
+        stats.solution_length = Some ( path.len() - 1 );
+        stats.solution_ratio  = path.len() as f32 / stats.open_cells as f32;
+
+        //経路上で、来た方向以外に二つ以上の道があるマスを数える
+        stats.decision_points = path.iter().take( path.len() - 1 ).enumerate()
+            .filter( | &( i, &cell ) |
+            {   let sides = self.get_sides_space_on( floor, cell ).len();
+                if i == 0 { sides >= 2 } else { sides >= 3 }
+            })
+            .count();
+
+        //経路から枝道の奥までの歩数を測る（経路の全マスを起点にした幅優先探索）
+        let mut visited: HashSet<IVec2> = path.iter().copied().collect();
+        let mut queue: VecDeque<( IVec2, usize )> = path.iter().map( | &cell | ( cell, 0 ) ).collect();
+        while let Some ( ( cell, depth ) ) = queue.pop_front()
+        {   stats.longest_branch = stats.longest_branch.max( depth );
+            for news in self.get_sides_space_on( floor, cell )
+            {   let next = cell + news;
+                if visited.insert( next ) { queue.push_back( ( next, depth + 1 ) ) }
+            }
+        }
+
+        //難易度の点数：分かれ道の多さ×枝道の深さ＋経路の長さ
+        let length = stats.solution_length.unwrap_or( 0 ).max( 1 ) as f32;
+        let branch = stats.longest_branch as f32 / length;
+        stats.difficulty = stats.decision_points as f32 * ( 1.0 + branch ) + length / 10.0;
+
+        stats
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
\ No newline at end of file
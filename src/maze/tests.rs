@@ -0,0 +1,619 @@
+use super::*;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//完全迷路を作るアルゴリズム
+const ALGORITHMS: [ Algorithm; 8 ] =
+[   Algorithm::Digger,
+    Algorithm::Prim,
+    Algorithm::Kruskal,
+    Algorithm::Wilson,
+    Algorithm::Eller,
+    Algorithm::HuntAndKill,
+    Algorithm::Sidewinder,
+    Algorithm::RecursiveDivision,
+];
+
+//試すシードの数と縦横幅の上限
+const CASES: u64 = 64;
+const SIZE_MAX: i32 = 61;
+
+//ブレイド率の試す値
+const BRAIDS: [ f32; 3 ] = [ 0.25, 0.5, 1.0 ];
+
+//シードと縦横幅(奇数・偶数・長方形を混ぜる)を変えて、全アルゴリズムで迷路を作る
+fn for_each_maze( check: impl Fn( &Map, &str ) )
+{   for_each_braided_maze( 0.0, check );
+}
+
+//ブレイド率を指定して迷路を作る
+fn for_each_braided_maze( braid: f32, check: impl Fn( &Map, &str ) )
+{   let mut sizes = StdRng::seed_from_u64( 0 );
+    for seed in 0..CASES
+    {   let size = IVec2::new
+        (   sizes.gen_range( MAP_GRIDS_MIN..=SIZE_MAX ),
+            sizes.gen_range( MAP_GRIDS_MIN..=SIZE_MAX ),
+        );
+        for algorithm in ALGORITHMS
+        {   let mut map = Map { braid, ..Map::new( size, algorithm ) };
+            map.reseed( seed );
+            map.generate();
+
+            let case = format!( "{algorithm:?} seed {seed} size {}x{} braid {braid}", size.x, size.y );
+            check( &map, &case );
+        }
+    }
+}
+
+//テキストを読み込むための空のMap（設定ファイルやコマンドライン引数に左右されない）
+fn empty_map() -> Map
+{   Map::new( IVec2::splat( MAP_GRIDS_MIN ), Algorithm::default() )
+}
+
+//空地の一覧
+fn open_cells( map: &Map ) -> Vec<IVec2>
+{   map.cells_where( | c | c.terrain == Terrain::Space ).collect()
+}
+
+//隣り合う空地の組の数
+fn count_edges( map: &Map ) -> usize
+{   open_cells( map ).iter()
+        .map( | &cell | [ News::East, News::South ].iter().filter( | &&news | map.is_space( cell + news ) ).count() )
+        .sum()
+}
+
+//startから全ての空地とgoalにたどり着けるか
+fn assert_reachable( map: &Map, case: &str )
+{   let mut visited = HashSet::from( [ map.start ] );
+    let mut queue = VecDeque::from( [ map.start ] );
+    while let Some ( cell ) = queue.pop_front()
+    {   for news in map.get_sides_space( cell )
+        {   if visited.insert( cell + news ) { queue.push_back( cell + news ) }
+        }
+    }
+
+    let open = open_cells( map );
+    assert!( map.is_space( map.start ), "{case}: start is not open" );
+    assert_eq!( visited.len(), open.len(), "{case}: unreachable open cells" );
+    assert!( map.goal.is_some_and( | goal | visited.contains( &goal ) ), "{case}: goal is unreachable" );
+}
+
+//外壁が壊れていないか
+fn assert_outer_walls( map: &Map, case: &str )
+{   for x in map.x_range()
+    {   for y in map.y_range()
+        {   if map.x_range_inner().contains( &x ) && map.y_range_inner().contains( &y ) { continue }
+            assert!( map.is_wall( IVec2::new( x, y ) ), "{case}: outer wall broken at {x},{y}" );
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn every_open_cell_is_reachable_from_start()
+{   for_each_maze( assert_reachable );
+}
+
+#[test]
+fn maze_is_perfect()
+{   //連結でループがない ⇔ 隣り合う空地の組の数 ＝ 空地の数 － 1
+    //（ブレイド率が0.0の場合だけ）
+    for_each_maze
+    (   | map, case |
+        assert_eq!( count_edges( map ) + 1, open_cells( map ).len(), "{case}: maze has loops" )
+    );
+}
+
+#[test]
+fn outer_walls_stay_intact()
+{   for_each_maze( assert_outer_walls );
+}
+
+#[test]
+fn deadend_flag_only_on_open_cells()
+{   for_each_maze
+    (   | map, case |
+        for cell in map.cells_where( | c | c.flags.contains( Flag::DeadEnd ) )
+        {   assert!( map.is_space( cell ), "{case}: dead-end flag on non-open cell {cell}" );
+        }
+    );
+}
+
+#[test]
+fn no_undefined_cells_remain()
+{   for_each_maze
+    (   | map, case |
+        {   let undef = map.cells_where( | c | c.terrain == Terrain::Undef ).count();
+            assert_eq!( undef, 0, "{case}: undefined cells remain" );
+        }
+    );
+}
+
+#[test]
+fn braided_maze_stays_connected_inside_outer_walls()
+{   for braid in BRAIDS
+    {   for_each_braided_maze
+        (   braid,
+            | map, case |
+            {   assert_reachable( map, case );
+                assert_outer_walls( map, case );
+            }
+        );
+    }
+}
+
+#[test]
+fn braid_turns_dead_ends_into_loops()
+{   for_each_braided_maze
+    (   1.0,
+        | map, case |
+        {   assert!( count_edges( map ) + 1 > open_cells( map ).len(), "{case}: braided maze has no loops" );
+
+            //残った行き止りは、外壁か道幅の制限で壊せないものだけ
+            for cell in map.dead_end_cells()
+            {   assert!( map.breakable_sides( cell ).is_empty(), "{case}: dead end left at {cell}" );
+            }
+        }
+    );
+}
+
+#[test]
+fn braid_does_not_widen_corridors()
+{   for_each_braided_maze
+    (   1.0,
+        | map, case |
+        for cell in open_cells( map )
+        {   let block = [ IVec2::ZERO, IVec2::X, IVec2::Y, IVec2::ONE ];
+            assert!( ! block.iter().all( | &d | map.is_space( cell + d ) ), "{case}: 2x2 open block at {cell}" );
+        }
+    );
+}
+
+#[test]
+fn bfs_and_astar_find_paths_of_equal_length()
+{   for_each_braided_maze
+    (   0.5,
+        | map, case |
+        {   let goal = map.goal.unwrap();
+            let bfs   = map.shortest_path_bfs  ( map.start, goal ).unwrap_or_else( || panic!( "{case}: no BFS path" ) );
+            let astar = map.shortest_path_astar( map.start, goal ).unwrap_or_else( || panic!( "{case}: no A* path" ) );
+            assert_eq!( bfs.len(), astar.len(), "{case}: path lengths differ" );
+
+            //経路はstartからgoalまで、空地を一歩ずつ進む
+            for path in [ &bfs, &astar ]
+            {   assert_eq!( ( path.first(), path.last() ), ( Some ( &map.start ), Some ( &goal ) ), "{case}" );
+                assert!( path.iter().all( | &cell | map.is_space( cell ) ), "{case}: path through a wall" );
+                let mut cell = map.start;
+                path_to_news( path ).into_iter().for_each( | news | cell += news );
+                assert_eq!( cell, goal, "{case}: directions do not lead to goal" );
+            }
+        }
+    );
+}
+
+#[test]
+fn unreachable_targets_have_no_path()
+{   //左右の部屋が壁で仕切られている
+    let mut map = empty_map();
+    map.load_ascii( "#######\n#S.#..#\n#..#..#\n#..#..#\n#######\n" ).unwrap();
+    let ( near, far, wall ) = ( IVec2::new( 2, 2 ), IVec2::new( 5, 2 ), IVec2::new( 3, 1 ) );
+
+    assert_eq!( map.shortest_path_bfs  ( map.start, near ).map( | path | path.len() ), Some ( 3 ) );
+    assert_eq!( map.shortest_path_astar( map.start, near ).map( | path | path.len() ), Some ( 3 ) );
+    for to in [ far, wall, IVec2::new( -1, 0 ) ]
+    {   assert_eq!( map.shortest_path_bfs  ( map.start, to ), None, "to {to}" );
+        assert_eq!( map.shortest_path_astar( map.start, to ), None, "to {to}" );
+    }
+}
+
+#[test]
+fn stats_of_a_hand_written_maze()
+{   //ゴールへは二歩。左下の枝道は四歩の深さで、下の通路とループになっている
+    let mut map = empty_map();
+    map.load_ascii( "#######\n#S.G..#\n#.#.###\n#.....#\n#######\n" ).unwrap();
+
+    let expected = MazeStats
+    {   open_cells     : 12,
+        dead_ends      : 2,  //(5,1) (5,3)
+        corridors      : 8,
+        junctions      : 2,  //(3,1) (3,3)
+        rooms          : 0,
+        solution_length: Some ( 2 ),
+        solution_ratio : 0.25, //3マス÷12マス
+        longest_branch : 4,  //ゴール(3,1)から(5,3)まで
+        decision_points: 1,  //スタートだけ
+        difficulty     : 3.2, //1×(1＋4÷2)＋2÷10
+    };
+    assert_eq!( map.stats(), expected );
+    assert_eq!( map.stats().rank(), Difficulty::Easy );
+}
+
+#[test]
+fn dungeon_is_connected_and_tags_rooms()
+{   let mut sizes = StdRng::seed_from_u64( 0 );
+    for seed in 0..CASES
+    {   let size = IVec2::new
+        (   sizes.gen_range( MAP_GRIDS_MIN..=SIZE_MAX ),
+            sizes.gen_range( MAP_GRIDS_MIN..=SIZE_MAX ),
+        );
+        let mut map = Map::new( size, Algorithm::Dungeon );
+        map.reseed( seed );
+        map.generate();
+
+        let case = format!( "Dungeon seed {seed} size {}x{}", size.x, size.y );
+        assert_reachable( &map, &case );
+        assert_outer_walls( &map, &case );
+
+        //部屋番号は空地にだけ付き、startは最初の部屋の中
+        let rooms: HashSet<u16> = open_cells( &map ).iter().filter_map( | &cell | map.room( cell ) ).collect();
+        assert!( map.cells_where( | c | c.flags.room().is_some() ).all( | cell | map.is_space( cell ) ), "{case}: room on wall" );
+        assert_eq!( map.room( map.start ), Some ( 0 ), "{case}: start is not in the first room" );
+        assert_eq!( rooms.len(), *rooms.iter().max().unwrap() as usize + 1, "{case}: room ids are not sequential" );
+    }
+}
+
+#[test]
+fn dungeon_text_round_trips_room_ids()
+{   for seed in 0..8
+    {   let mut map = Map { floors: 2, ..Map::new( IVec2::new( 41, 31 ), Algorithm::Dungeon ) };
+        map.reseed( seed );
+        map.generate();
+        let text = map.to_ascii();
+
+        let case = format!( "Dungeon seed {seed}" );
+        let mut loaded = empty_map();
+        loaded.load_ascii( &text ).unwrap_or_else( | err | panic!( "{case}: {err}" ) );
+        assert!( loaded.matrices == map.matrices, "{case}: cells differ" );
+        assert_eq!( loaded.room( loaded.start ), Some ( 0 ), "{case}" );
+    }
+
+    //部屋番号の上限を超えたらエラー
+    let maze = "#####\n#S..#\n#...#\n#...#\n#####\n";
+    let err = empty_map().load_ascii( &format!( "{maze}@1,2,2 room={ROOM_MAX}\n" ) ).unwrap_err();
+    assert_eq!( ( err.line, err.column ), ( 6, 8 ), "{err}" );
+}
+
+#[test]
+fn cave_keeps_only_the_region_of_start()
+{   let mut sizes = StdRng::seed_from_u64( 0 );
+    for seed in 0..CASES
+    {   let size = IVec2::new
+        (   sizes.gen_range( MAP_GRIDS_MIN..=SIZE_MAX ),
+            sizes.gen_range( MAP_GRIDS_MIN..=SIZE_MAX ),
+        );
+        let mut map = Map::new( size, Algorithm::Cave );
+        map.reseed( seed );
+        map.generate();
+
+        let case = format!( "Cave seed {seed} size {}x{}", size.x, size.y );
+        assert_reachable( &map, &case );
+        assert_outer_walls( &map, &case );
+        assert_eq!( map.cells_where( | c | c.terrain == Terrain::Undef ).count(), 0, "{case}: undefined cells remain" );
+    }
+}
+
+#[test]
+fn masked_maze_stays_inside_the_shape()
+{   //左の「L」と右の点は離れている
+    let mask = MazeMask::from_rows
+    (   &[  "##      ",
+            "##      ",
+            "##    ##",
+            "####  ##",
+            "####    ",
+        ]
+    ).unwrap();
+
+    for seed in 0..CASES
+    {   let mut map = Map { braid: 0.5, mask: Some ( mask.clone() ), ..Map::new( IVec2::new( 33, 21 ), Algorithm::Prim ) };
+        assert!( map.check_algorithm().is_err() );
+        map.reseed( seed );
+        map.generate();
+
+        //マスクに対応していないPrimの代わりに、実際に使った穴掘り法が残る
+        let case = format!( "mask seed {seed}" );
+        assert_eq!( map.algorithm, Algorithm::Digger, "{case}: used algorithm was not recorded" );
+        assert!( map.check_algorithm().is_ok(), "{case}" );
+        assert_reachable( &map, &case );
+        assert_outer_walls( &map, &case );
+        assert!( map.is_in_mask( map.start ), "{case}: start is outside the mask" );
+        for cell in open_cells( &map )
+        {   assert!( map.is_in_mask( cell ), "{case}: open cell {cell} is outside the mask" );
+            assert!( cell.x < 20, "{case}: unreachable part of the mask was dug at {cell}" );
+        }
+        assert!( open_cells( &map ).len() > 50, "{case}: maze is too small" );
+    }
+}
+
+#[test]
+fn mask_text_skips_comments_and_keeps_blank_rows()
+{   let mask = MazeMask::from_ascii( "//comment\n #\n\n# .\n\n" ).unwrap();
+    assert_eq!( mask, MazeMask::from_rows( &[ " # ", "", "#  " ] ).unwrap() );
+    assert!( MazeMask::from_ascii( "//comment\n . \n" ).is_err() );
+}
+
+//テスト用のプレハブ（スタートの部屋、ゴールの部屋、柱のある広間）
+const TEST_PREFABS: &str = "\
+//start
+...
+.S.
+...
+
+//goal
+...
+.G.
+...
+
+.......
+.#...#.
+.......
+??...??
+";
+
+#[test]
+fn prefabs_are_embedded_and_connected()
+{   let prefabs = Prefab::from_ascii( TEST_PREFABS ).unwrap();
+    assert_eq!( prefabs.len(), 3 );
+    let prefab_spaces = 9 + 9 + 22;
+
+    for seed in 0..CASES
+    {   let mut map = Map { braid: 0.3, prefabs: prefabs.clone(), ..Map::new( IVec2::new( 31, 25 ), Algorithm::Digger ) };
+        map.reseed( seed );
+        map.generate();
+
+        let case = format!( "prefabs seed {seed}" );
+        assert_reachable( &map, &case );
+        assert_outer_walls( &map, &case );
+
+        //プレハブの空地はそのまま残り、スタートとゴールはプレハブの中
+        let spaces = map.cells_where( | c | c.terrain == Terrain::Space && c.flags.contains( Flag::Prefab ) ).count();
+        assert_eq!( spaces, prefab_spaces, "{case}: prefab open cells changed" );
+        assert!( map.has_flag( map.start, Flag::Prefab ), "{case}: start is not in the start room" );
+        assert!( map.goal.is_some_and( | goal | map.has_flag( goal, Flag::Prefab ) ), "{case}: goal is not in the goal room" );
+        assert!( open_cells( &map ).len() > prefab_spaces * 4, "{case}: labyrinth was not dug" );
+
+        //テキストにしても、プレハブのフラグは残る
+        let mut loaded = empty_map();
+        loaded.load_ascii( &map.to_ascii() ).unwrap();
+        assert!( loaded.matrices == map.matrices, "{case}: reloaded cells differ" );
+    }
+}
+
+#[test]
+fn prefab_goal_is_always_reachable()
+{   //マスクが左右に分かれているので、ゴールのプレハブがスタートと反対側に置かれることがある
+    let mask = MazeMask::from_ascii( "##.##\n##.##\n" ).unwrap();
+    let prefabs = Prefab::from_ascii( "...\n.G.\n...\n" ).unwrap();
+    for seed in 0..CASES
+    {   let mut map = Map { mask: Some ( mask.clone() ), prefabs: prefabs.clone(), ..Map::new( IVec2::new( 23, 13 ), Algorithm::Digger ) };
+        map.reseed( seed );
+        map.generate();
+
+        let goal = map.goal.unwrap();
+        assert!( map.shortest_path_bfs( map.start, goal ).is_some(), "seed {seed}: goal {goal} is unreachable" );
+    }
+}
+
+#[test]
+fn prefab_text_errors_point_at_the_line()
+{   let err = Prefab::from_ascii( "//c\n.S.\n.x.\n" ).unwrap_err();
+    assert_eq!( ( err.line, err.column ), ( 3, 2 ) );
+
+    let err = Prefab::from_ascii( "...\n\n.#.\n#.#\n" ).unwrap_err();
+    assert_eq!( err.line, 3, "{err}" ); //空地が離れている
+    assert!( Prefab::from_ascii( "S.S\n" ).is_err() );
+}
+
+#[test]
+fn room_id_keeps_other_flags()
+{   let mut flags = FlagSet::default();
+    flags.insert( Flag::Visited );
+    assert_eq!( flags.room(), None );
+
+    flags.set_room( 0 );
+    flags.set_room( 300 );
+    flags.insert( Flag::Seen );
+    assert_eq!( flags.room(), Some ( 300 ) );
+    assert!( flags.contains( Flag::Visited ) && flags.contains( Flag::Seen ) );
+    assert!( ! flags.contains( Flag::DeadEnd ) );
+}
+
+#[test]
+fn flag_set_inserts_removes_and_lists_every_flag()
+{   //全部のフラグを立てると、宣言順にすべて列挙される
+    let mut flags = FlagSet::default();
+    FLAGS.iter().for_each( | &flag | flags.insert( flag ) );
+    flags.set_room( 7 );
+    assert_eq!( flags.iter().collect::<Vec<_>>(), FLAGS );
+
+    //一つずつ下ろすと、そのフラグだけが消えて部屋番号は残る
+    for ( i, &flag ) in FLAGS.iter().enumerate()
+    {   flags.remove( flag );
+        flags.remove( flag ); //二度下ろしても変わらない
+        assert!( ! flags.contains( flag ) );
+        assert_eq!( flags.iter().collect::<Vec<_>>(), FLAGS[ i + 1.. ] );
+        assert_eq!( flags.room(), Some ( 7 ) );
+    }
+    assert_eq!( flags.iter().count(), 0 );
+}
+
+#[test]
+fn zero_braid_keeps_the_same_maze()
+{   let make = | braid |
+    {   let mut map = Map { braid, ..Map::new( IVec2::splat( 31 ), Algorithm::Digger ) };
+        map.reseed( 1 );
+        map.generate();
+        map.to_ascii()
+    };
+    assert_eq!( make( 0.0 ), make( 0.0 ) );
+    assert_ne!( make( 0.0 ), make( 0.5 ) );
+}
+
+#[test]
+fn floors_are_linked_by_paired_stairs()
+{   let algorithms = ALGORITHMS.into_iter().chain( [ Algorithm::Dungeon, Algorithm::Cave ] );
+    for ( seed, algorithm ) in algorithms.enumerate()
+    {   let mut map = Map { floors: 3, ..Map::new( IVec2::new( 31, 21 ), algorithm ) };
+        map.reseed( seed as u64 );
+        map.generate();
+
+        let case = format!( "{algorithm:?} seed {seed} floors 3" );
+        assert_eq!( ( map.floor(), map.floor_count(), map.goal_floor ), ( 0, 3, 2 ), "{case}" );
+
+        //上り階段は各階に一つで、真上の階の同じマスが下り階段
+        for floor in 0..map.floor_count()
+        {   map.set_floor( floor );
+            let ups  : Vec<IVec2> = map.cells_where( | c | c.flags.contains( Flag::StairsUp   ) ).collect();
+            let downs: Vec<IVec2> = map.cells_where( | c | c.flags.contains( Flag::StairsDown ) ).collect();
+            assert_eq!( ups.len()  , usize::from( floor < 2 ), "{case}: floor {floor} stairs up" );
+            assert_eq!( downs.len(), usize::from( floor > 0 ), "{case}: floor {floor} stairs down" );
+            assert_outer_walls( &map, &case );
+
+            if let Some ( &up ) = ups.first()
+            {   assert!( map.is_space( up ), "{case}: stairs up in a wall" );
+                map.set_floor( floor + 1 );
+                assert!( map.has_flag( up, Flag::StairsDown ), "{case}: floor {floor} stairs are not paired" );
+            }
+        }
+
+        //階段を通ってゴールまで行ける
+        assert!( map.solution_length().is_some(), "{case}: goal is unreachable" );
+
+        //階を指定した分析は、その階に移って分析したものと同じ
+        let floor_stats = map.floor_stats();
+        for ( floor, stats ) in floor_stats.iter().enumerate()
+        {   map.set_floor( floor );
+            assert_eq!( &map.stats(), stats, "{case}: floor {floor} stats differ" );
+        }
+    }
+}
+
+#[test]
+fn multi_floor_text_round_trips()
+{   let mut map = Map { floors: 3, ..Map::new( IVec2::new( 21, 11 ), Algorithm::Prim ) };
+    map.reseed( 5 );
+    map.generate();
+    let text = map.to_ascii();
+
+    let mut loaded = empty_map();
+    loaded.load_ascii( &text ).unwrap();
+    assert_eq!( ( loaded.floor_count(), loaded.goal_floor, loaded.goal ), ( 3, 2, map.goal ) );
+    assert!( loaded.matrices == map.matrices );
+    assert_eq!( loaded.to_ascii(), text );
+    assert_eq!( loaded.solution_length(), map.solution_length() );
+}
+
+#[test]
+fn text_round_trips_every_cell_flag()
+{   for ( seed, algorithm ) in ALGORITHMS.into_iter().enumerate()
+    {   let mut map = Map { floors: 2, braid: 0.5, ..Map::new( IVec2::new( 23, 17 ), algorithm ) };
+        map.reseed( seed as u64 );
+        map.generate();
+
+        //文字で表せないフラグも立てておく（階段や行き止りと重なるものも含む）
+        let mut rng = StdRng::seed_from_u64( seed as u64 );
+        for floor in 0..map.floor_count()
+        {   map.set_floor( floor );
+            for cell in open_cells( &map )
+            {   if rng.gen_bool( 0.3 ) { map.add_flag( cell, Flag::Visited ) }
+                if rng.gen_bool( 0.3 ) { map.add_flag( cell, Flag::Seen ) }
+            }
+        }
+        map.set_floor( 0 );
+
+        let case = format!( "{algorithm:?} seed {seed}" );
+        let mut loaded = empty_map();
+        loaded.load_ascii( &map.to_ascii() ).unwrap_or_else( | err | panic!( "{case}: {err}" ) );
+        assert!( loaded.matrices == map.matrices, "{case}: cells differ" );
+        assert_eq!( ( loaded.start, loaded.goal, loaded.goal_floor ), ( map.start, map.goal, map.goal_floor ), "{case}" );
+    }
+}
+
+#[test]
+fn text_errors_point_at_the_line_and_column()
+{   let maze = "//c\n#####\n#S..#\n#...#\n#..G#\n#####\n";
+    let check = | text: String, position |
+    {   let err = empty_map().load_ascii( &text ).unwrap_err();
+        assert_eq!( ( err.line, err.column ), position, "{err}" );
+    };
+    check( maze.replacen( "#...#", "#.x.#", 1 ), ( 4, 3 ) ); //知らない文字
+    check( maze.replacen( "#...#", "#..#", 1 ), ( 4, 5 ) );  //行の長さが違う
+    check( maze.replacen( "G", "S", 1 ), ( 5, 4 ) );         //スタートが二つ
+
+    //フラグの行
+    check( format!( "{maze}@1,1,1 Seen Bogus\n" ), ( 7, 13 ) ); //知らないフラグ
+    check( format!( "{maze}@2,1,1 Seen\n" ), ( 7, 2 ) );        //ない階
+    check( format!( "{maze}@1,5,1 Seen\n" ), ( 7, 2 ) );        //マップの外
+    check( format!( "{maze}\n@\n" ), ( 8, 1 ) );               //位置がない
+}
+
+#[test]
+fn unpaired_stairs_and_upper_start_are_rejected()
+{   let floor = "#####\n#...#\n#...#\n#...#\n#####\n";
+    let err = empty_map().load_ascii( &format!( "#####\n#S.<#\n#...#\n#...#\n#####\n\n{floor}" ) ).unwrap_err();
+    assert_eq!( ( err.line, err.column ), ( 2, 4 ), "{err}" );
+
+    let err = empty_map().load_ascii( &format!( "{}\n//2F\n#####\n#.S.#\n#...#\n#...#\n#####\n", floor.replacen( '.', "S", 1 ) ) ).unwrap_err();
+    assert_eq!( ( err.line, err.column ), ( 9, 3 ), "{err}" );
+
+    let err = empty_map().load_ascii( &format!( "{}\n#####\n#...#\n#####\n", floor.replacen( '.', "S", 1 ) ) ).unwrap_err();
+    assert_eq!( err.line, 7, "{err}" ); //階の縦幅が違う
+}
+
+#[test]
+fn labyrinth_steps_dig_every_open_cell_once()
+{   let mut map = Map::new( IVec2::new( 31, 21 ), Algorithm::Digger );
+    map.reseed( 7 );
+    map.fill_walls();
+    map.start = IVec2::new( 15, 11 );
+    map.set_space( map.start );
+
+    let steps: Vec<BuildStep> = map.labyrinth().collect();
+    let digs: HashSet<IVec2> = steps.iter().filter_map( | step | match step { BuildStep::Dig ( cell ) => Some ( *cell ), _ => None } ).collect();
+    let digs_count = steps.iter().filter( | step | matches!( step, BuildStep::Dig ( _ ) ) ).count();
+    assert_eq!( digs.len(), digs_count, "a cell was dug twice" );
+    assert_eq!( digs.len() + 1, open_cells( &map ).len() );
+    assert!( steps.iter().all( | step | ! matches!( step, BuildStep::Fill ( _ ) ) ) );
+    assert!( steps.iter().any( | step | matches!( step, BuildStep::Backtrack ( _ ) ) ) );
+    assert_eq!( map.labyrinth().next(), None, "finished labyrinth must not dig again" );
+}
+
+#[test]
+fn build_log_replays_to_the_same_maze()
+{   let algorithms = ALGORITHMS.into_iter().chain( [ Algorithm::Dungeon, Algorithm::Cave ] );
+    for ( seed, algorithm ) in algorithms.enumerate()
+    {   let mut map = Map { floors: 2, braid: 0.5, build_log: Some ( Vec::new() ), ..Map::new( IVec2::new( 25, 19 ), algorithm ) };
+        map.reseed( seed as u64 );
+        map.generate();
+
+        //全部壁の状態から手順どおりに掘り・埋めると、できた迷路と同じになる
+        let case = format!( "{algorithm:?} seed {seed}" );
+        let log = map.build_log.clone().unwrap();
+        let mut walls = vec![ HashSet::new(); map.floor_count() ];
+        for floor in walls.iter_mut()
+        {   floor.extend( map.x_range().flat_map( | x | map.y_range().map( move | y | IVec2::new( x, y ) ) ) );
+        }
+        for &( floor, step ) in log.iter()
+        {   match step
+            {   BuildStep::Dig  ( cell ) => assert!( walls[ floor ].remove( &cell ), "{case}: dug an open cell" ),
+                BuildStep::Fill ( cell ) => assert!( walls[ floor ].insert( cell ), "{case}: filled a wall" ),
+                BuildStep::Backtrack ( cell ) => assert!( ! walls[ floor ].contains( &cell ), "{case}: backtracked into a wall" ),
+            }
+        }
+        for ( floor, walls ) in walls.iter().enumerate()
+        {   map.set_floor( floor );
+            let expected: HashSet<IVec2> = map.cells_where( | c | c.terrain == Terrain::Wall ).collect();
+            assert_eq!( walls, &expected, "{case}: floor {floor} differs" );
+        }
+
+        //同じシードで作り直すと、記録も最初から同じになる
+        map.reseed( seed as u64 );
+        map.generate();
+        assert_eq!( map.build_log.as_ref(), Some ( &log ), "{case}: log was not restarted" );
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
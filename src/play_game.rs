@@ -3,8 +3,18 @@ use super::*;
 //internal submodules
 mod map;
 mod player;
+mod minimap;
+mod automap;
+mod menu;
+mod progress;
+mod stage_clear;
+mod watch;
+pub use map::{ MazeLevel, MazeLevelLoader, LoadedMazeLevel };
 use player::{ FppCamera, TppCamera };
 
+#[cfg( test )]
+mod tests;
+
 ////////////////////////////////////////////////////////////////////////////////
 
 //プラグインの設定
@@ -17,7 +27,17 @@ impl Plugin for Schedule
 
         //Resourceの登録
         .init_resource::<map::Map>()    //Map情報
+        .init_resource::<MazeSeed>()    //迷路の乱数シード
         .init_resource::<OrbitCamera>() //極座標カメラ情報
+        .init_resource::<StageRecord>() //ステージの記録
+        .init_resource::<automap::AutoMap>() //オートマップの状態
+        .init_resource::<menu::GameMenu>()   //ゲームメニューの状態
+        .init_resource::<progress::GameMode>() //ゲームモード
+        .init_resource::<Progress>()           //レベルと累計スコア
+        .init_resource::<watch::Watch>()       //見物モードの状態
+
+        //Eventの登録
+        .add_event::<menu::NewMaze>() //新しい迷路を作る
 
         //ゲームプレイ前の処理
         .add_systems
@@ -32,14 +52,22 @@ impl Plugin for Schedule
         //ステージの前処理
         .add_systems
         (   OnEnter ( MyState::MainLoop ),
-            (   map::make_new_data,   //新しいMapデータを作る
+            (   progress::apply_level, //エンドレスモードならレベルで迷路の大きさ等を決める
+                watch::start_recording, //見物モードなら迷路を作る手順を記録させる
+                map::make_new_data,   //新しいMapデータを作る
+                progress::reject_easy_maze, //エンドレスモードなら易しすぎる迷路を作り直す
                 map::spawn_entity,    //Mapを3D表示する
                 player::spawn_entity, //playerと3Dカメラのspawn
+                watch::spawn_entity,  //見物モードなら迷路を作る手順の再生を始める
+                minimap::spawn_sprite, //ミニマップのspawn
+                automap::spawn_sprite, //オートマップのspawn(非表示)
 
                 //Playerカメラ(Fpp&Tpp)がない場合(ほぼデバッグ時)、
                 //AppDefaultな3Dカメラ(があれば)をmapのstartへ向ける(※1)
                 look_at_map_start::<misc::AppDefault3dCamera>
                     .run_if( any_with_component::<misc::AppDefault3dCamera>() ),
+
+                reset_stage_record, //ステージの記録を初期化
             )
             .chain() //実行順の固定
         )
@@ -61,16 +89,118 @@ impl Plugin for Schedule
                     (   player::rotate_player, //playerの向きを変える
                         player::move_player,   //playerを移動する
                     ),
+                    minimap::update_image, //ミニマップを描き直す
+                    player::check_goal, //ゴールに着いたらステージクリア
+                )
+                .chain()
+                .run_if( not( automap::is_open ) ) //オートマップ表示中は操作しない
+                .run_if( not( menu::is_open ) )    //メニュー表示中は操作しない
+                .run_if( not( watch::is_watching ) ), //見物モードの再生中は操作しない
+
+                //[M]キーでオートマップを開閉する
+                (   automap::toggle,
+                    (   automap::catch_input_keyboard, //カーソル移動・拡大縮小・印
+                        automap::update_image,         //オートマップを描き直す
+                    )
+                    .chain()
+                    .run_if( automap::is_open ),
                 )
                 .chain()
+                .run_if( not( menu::is_open ) )
+                .run_if( not( watch::is_watching ) ),
+
+                //[Tab]キーでメニューを開閉する
+                (   menu::toggle.run_if( not( automap::is_open ) ),
+                    (   menu::catch_input, //項目の選択・シードの入力
+                        menu::update_ui,   //項目の表示を更新
+                    )
+                    .chain()
+                    .run_if( menu::is_open ),
+                )
+                .chain(),
+
+                map::spin_goal,     //ゴールの目印を回す
+                map::show_floors,   //今の階より上の階を隠す
+                count_stage_time    //ステージの経過時間を数える
+                    .run_if( not( watch::is_watching ) ),
+
+                //見物モード：迷路を作る手順を再生する（[+][-]キーで速さを変え、[Enter]キーで飛ばす）
+                (   (   watch::catch_input.run_if( not( menu::is_open ) ),
+                        watch::play,
+                    )
+                    .chain()
+                    .run_if( watch::is_watching ),
+                    watch::update_view, //再生中は迷路全体を見下ろし、終わったら一人称視点に戻す
+                )
+                .chain(),
+
+                (   //[N]キーで新しい迷路を作る
+                    menu::catch_new_maze_shortcut,
+
+                    //[R]キーで同じシードの迷路を作り直す
+                    regenerate_same_maze,
+
+                    //[P]キーで迷路をテキスト形式で書き出す
+                    map::export_ascii,
+                )
+                .run_if( not( menu::is_open ) ),
+
+                //新しい迷路を作る（メニューかショートカットから）
+                menu::start_new_maze,
             )
             .run_if( in_state( MyState::MainLoop ) )
+        )
+
+        //ステージクリア（結果を表示し、次の迷路へ進む）
+        .add_systems
+        (   OnEnter ( MyState::StageClear ),
+            (   progress::add_stage_score,  //得点を累計に加える
+                stage_clear::spawn_results, //結果画面を表示
+            )
+            .chain()
+        )
+        .add_systems
+        (   Update,
+            (   map::spin_goal,            //ゴールの目印を回す
+                stage_clear::goto_next_maze, //キー入力か時間経過で次の迷路へ
+            )
+            .run_if( in_state( MyState::StageClear ) )
+        )
+        .add_systems
+        (   OnExit ( MyState::StageClear ),
+            (   misc::despawn::<stage_clear::StageClearUi>, //結果画面を削除
+                stage_clear::forget_loaded_level, //次の迷路は新しく作る
+                progress::level_up, //エンドレスモードならレベルを上げる
+            )
         );
     }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 
+//ステージの記録のResource
+#[derive( Resource, Default )]
+pub struct StageRecord
+{   pub steps: u32, //歩数
+    pub time : f32, //経過時間(秒)
+    pub score: u32, //得点（クリア時に計算する）
+}
+
+//ステージの記録を初期化する
+fn reset_stage_record( mut record: ResMut<StageRecord> )
+{   *record = StageRecord::default();
+}
+
+//ステージの経過時間を数える
+fn count_stage_time
+(   mut record: ResMut<StageRecord>,
+    time: Res<Time>,
+)
+{   record.time += time.delta().as_secs_f32();
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
 //テスト用：カメラをmap.startへ向ける
 fn look_at_map_start<T: Component>
 (   mut que_camera: Query<(&mut Transform, &Camera), With<T>>,
@@ -95,6 +225,19 @@ fn look_at_map_start<T: Component>
 
 ////////////////////////////////////////////////////////////////////////////////
 
+//同じシードで迷路を作り直す
+//（GameStartを経由してMainLoopへ入り直すと、OnEnterの処理で全て作り直される）
+fn regenerate_same_maze
+(   mut next_state: ResMut<NextState<MyState>>,
+    inkey: Res<Input<KeyCode>>,
+)
+{   if ! inkey.just_pressed( KeyCode::R ) { return } //[R]キー
+
+    next_state.set( MyState::GameStart );
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
 //テスト用：一人称視点カメラ⇔三人称視点カメラ切替
 fn switch_fpp_and_tpp
 (   mut que_cameras: Query<&mut Camera, Or<( &FppCamera, &TppCamera )>>, 
@@ -0,0 +1,173 @@
+use super::*;
+
+//external crates
+use bevy::render::render_resource::{ Extent3d, TextureDimension, TextureFormat };
+
+////////////////////////////////////////////////////////////////////////////////
+
+//オートマップのComponent
+#[derive( Component )]
+pub struct AutoMapSprite;
+
+//オートマップの状態のResource
+#[derive( Resource, Default )]
+pub struct AutoMap
+{   pub is_open: bool,          //表示中か
+    cursor     : IVec2,         //カーソルのマス（表示の中心）
+    zoom       : usize,         //AUTOMAP_ZOOMSの添字
+    markers    : HashSet<( usize, IVec2 )>, //プレイヤーが付けた印（階とマス）
+}
+
+//オートマップの設定
+const AUTOMAP_TEXTURE_SCALE: f32 = 0.5; //表示エリアに対するテクスチャの解像度
+const AUTOMAP_ZOOMS: [ i32; 7 ] = [ 2, 3, 4, 6, 8, 12, 16 ]; //1マスのテクスチャ上のPixel数
+const COLOR_CURSOR: [ u8; 4 ] = [ 255, 255, 255, 255 ];
+const COLOR_MARKER: [ u8; 4 ] = [   0, 255, 255, 255 ];
+
+////////////////////////////////////////////////////////////////////////////////
+
+//オートマップのスプライトをspawnする（非表示で作り、[M]キーで表示を切り替える）
+pub fn spawn_sprite
+(   q_automap: Query<Entity, With<AutoMapSprite>>,
+    mut automap: ResMut<AutoMap>,
+    mut cmds: Commands,
+    mut images: ResMut<Assets<Image>>,
+)
+{   //既存のオートマップがあれば削除し、印も消す
+    q_automap.for_each( | id | cmds.entity( id ).despawn_recursive() );
+    *automap = AutoMap::default();
+
+    let pixels = ( SCREEN_FRAME.size * AUTOMAP_TEXTURE_SCALE ).as_uvec2();
+    let size = Extent3d { width: pixels.x, height: pixels.y, depth_or_array_layers: 1 };
+    let image = Image::new_fill
+    (   size,
+        TextureDimension::D2,
+        &minimap::COLOR_UNKNOWN,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+
+    //3Dの表示エリア(viewport)にぴったり重ねる
+    let custom_size = Some ( SCREEN_FRAME.size );
+    let window = Vec2::new( SCREEN_PIXELS_WIDTH, SCREEN_PIXELS_HEIGHT );
+    let center = SCREEN_FRAME.zero + SCREEN_FRAME.size / 2.0 - window / 2.0;
+    let vec3 = Vec3::new( center.x, - center.y, DEPTH_SPRITE_AUTOMAP );
+
+    cmds.spawn( ( SpriteBundle::default(), AutoMapSprite ) )
+    .insert( Sprite { custom_size, ..default() } )
+    .insert( Transform::from_translation( vec3 ) )
+    .insert( Visibility::Hidden )
+    .insert( images.add( image ) )
+    ;
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//オートマップが表示中か（run_if用）
+pub fn is_open( automap: Res<AutoMap> ) -> bool { automap.is_open }
+
+//[M]キーでオートマップの表示を切り替える
+pub fn toggle
+(   q_player: Query<&player::Player>,
+    mut q_automap: Query<&mut Visibility, With<AutoMapSprite>>,
+    mut automap: ResMut<AutoMap>,
+    map: Res<map::Map>,
+    inkey: Res<Input<KeyCode>>,
+)
+{   if ! inkey.just_pressed( KeyCode::M ) { return } //[M]キー
+    let Ok ( player ) = q_player.get_single() else { return };
+    if ! automap.is_open && ! player.is_stop() { return } //移動中は開かない
+    let Ok ( mut visibility ) = q_automap.get_single_mut() else { return };
+
+    automap.is_open = ! automap.is_open;
+    *visibility = if automap.is_open { Visibility::Visible } else { Visibility::Hidden };
+
+    //開くときは、Playerを中心にマップ全体が収まる倍率にする
+    if automap.is_open
+    {   let texture = SCREEN_FRAME.size * AUTOMAP_TEXTURE_SCALE;
+        let fit = ( texture.x as i32 / map.width() ).min( texture.y as i32 / map.height() );
+        automap.cursor = player.position();
+        automap.zoom = AUTOMAP_ZOOMS.iter().rposition( | &zoom | zoom <= fit ).unwrap_or( 0 );
+    }
+}
+
+//オートマップ表示中のキー入力
+//[←↑↓→]カーソル移動(表示も動く)、[+][-]拡大縮小、[Enter]印を付ける・消す
+pub fn catch_input_keyboard
+(   mut automap: ResMut<AutoMap>,
+    map: Res<map::Map>,
+    inkey: Res<Input<KeyCode>>,
+)
+{   for keycode in inkey.get_just_pressed()
+    {   match keycode
+        {   KeyCode::Up    => automap.cursor += News::North,
+            KeyCode::Right => automap.cursor += News::East,
+            KeyCode::Left  => automap.cursor += News::West,
+            KeyCode::Down  => automap.cursor += News::South,
+            KeyCode::Equals | KeyCode::Plus | KeyCode::NumpadAdd =>
+                automap.zoom = ( automap.zoom + 1 ).min( AUTOMAP_ZOOMS.len() - 1 ),
+            KeyCode::Minus | KeyCode::NumpadSubtract =>
+                automap.zoom = automap.zoom.saturating_sub( 1 ),
+            KeyCode::Return =>
+            {   let marker = ( map.floor(), automap.cursor );
+                if ! automap.markers.remove( &marker ) { automap.markers.insert( marker ); }
+            }
+            _ => (),
+        }
+    }
+
+    //カーソルはマップの外に出さない（変更検知で描き直すので、はみ出した時だけ書き換える）
+    let max = IVec2::new( map.width(), map.height() ) - 1;
+    let cursor = automap.cursor.clamp( IVec2::ZERO, max );
+    if cursor != automap.cursor { automap.cursor = cursor }
+}
+
+//オートマップを描き直す（表示中に状態が変わったら）
+pub fn update_image
+(   q_player: Query<&player::Player>,
+    q_automap: Query<&Handle<Image>, With<AutoMapSprite>>,
+    automap: Res<AutoMap>,
+    map: Res<map::Map>,
+    mut images: ResMut<Assets<Image>>,
+)
+{   if ! automap.is_changed() || ! automap.is_open { return }
+    let Ok ( player ) = q_player.get_single() else { return };
+    let Ok ( handle ) = q_automap.get_single() else { return };
+    let Some ( image ) = images.get_mut( handle ) else { return };
+
+    //背景を塗る
+    let width  = image.texture_descriptor.size.width  as i32;
+    let height = image.texture_descriptor.size.height as i32;
+    image.data.chunks_exact_mut( 4 ).for_each( | pixel | pixel.copy_from_slice( &minimap::COLOR_UNKNOWN ) );
+
+    //カーソルを中心に、表示エリアに入るマスを塗る
+    let zoom = AUTOMAP_ZOOMS[ automap.zoom ];
+    let half = IVec2::new( width, height ) / zoom / 2 + 1;
+    let center = IVec2::new( width, height ) / 2 - zoom / 2;
+    let to_pixel = | cell: IVec2 | center + ( cell - automap.cursor ) * zoom;
+    for y in automap.cursor.y - half.y..=automap.cursor.y + half.y
+    {   for x in automap.cursor.x - half.x..=automap.cursor.x + half.x
+        {   let cell = IVec2::new( x, y );
+            if ! map.x_range().contains( &x ) || ! map.y_range().contains( &y ) { continue }
+            minimap::paint( image, to_pixel( cell ), zoom, | _ | true, minimap::cell_color( &map, cell ) );
+        }
+    }
+
+    //今の階の印（マスの内側の小さい四角）
+    let inset = ( zoom / 4 ).max( 1 );
+    let is_inner = | IVec2 { x, y }: IVec2 | x >= inset && x < zoom - inset && y >= inset && y < zoom - inset;
+    for &( _, marker ) in automap.markers.iter().filter( | ( floor, _ ) | *floor == map.floor() )
+    {   minimap::paint( image, to_pixel( marker ), zoom, is_inner, COLOR_MARKER );
+    }
+
+    //Playerの位置と向き
+    let is_arrow = minimap::arrow( player.direction(), zoom );
+    minimap::paint( image, to_pixel( player.position() ), zoom, is_arrow, minimap::COLOR_PLAYER );
+
+    //カーソル（マスの枠）
+    let is_frame = | IVec2 { x, y }: IVec2 | x == 0 || y == 0 || x == zoom - 1 || y == zoom - 1;
+    minimap::paint( image, to_pixel( automap.cursor ), zoom, is_frame, COLOR_CURSOR );
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
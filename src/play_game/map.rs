@@ -1,226 +1,92 @@
 use super::*;
 
-////////////////////////////////////////////////////////////////////////////////
-
-//MapのResource
-#[derive( Resource )]
-pub struct Map
-{   rng: rand::prelude::StdRng, //専用乱数発生器
-    matrix: Vec<Vec<Flag>>,     //map
-    pub start: IVec2,           //スタート位置
-}
-
-//マスの情報
-#[derive( Clone )]
-struct Flag ( u128 );
-
-//Map::default()の定義
-impl Default for Map
-{   fn default() -> Self
-    {   let seed_dev = 1234567890;
-        let seed_rel = || rand::thread_rng().gen::<u64>();
-        let seed = if misc::DEBUG() { seed_dev } else { seed_rel() };
+//迷路の中核（迷路データ・生成・分析・テキスト形式）はライブラリにある
+pub use chip14::maze::*;
 
-        let cell = Flag ( BIT_CELL_UNDEF );
-        let column = vec![ cell  ; MAP_GRIDS_HEIGHT as usize ];
-        let matrix = vec![ column; MAP_GRIDS_WIDTH  as usize ];
-
-        Self
-        {   rng  : StdRng::seed_from_u64( seed ),
-            matrix,
-            start: IVec2::default(),
-        }
-    }
-}
+//internal submodules
+mod level;
+pub use level::*;
 
-//マス目の状態を表すビット(フラグは128個まで)
-const BIT_CELL_UNDEF  : u128 = 0b000; //未定義
-const BIT_CELL_SPACE  : u128 = 0b001; //地形：空地
-const BIT_CELL_WALL   : u128 = 0b010; //地形：壁
-const BIT_FLAG_DEADEND: u128 = 0b100; //フラグ：行き止り
+mod mesh;
+pub use mesh::*;
 
 ////////////////////////////////////////////////////////////////////////////////
 
-//Mapのメソッド
-impl Map
-{   //ユーティリティ
-    fn is_inside( &self, cell: IVec2 ) -> bool
-    {   MAP_GRIDS_X_RANGE.contains( &cell.x ) &&
-        MAP_GRIDS_Y_RANGE.contains( &cell.y )
-    }
-    fn matrix_mut( &mut self, IVec2 { x, y }: IVec2 ) -> &mut Flag
-    {   &mut self.matrix[ x as usize ][ y as usize ]
-    }
-    fn matrix( &self, IVec2 { x, y }: IVec2 ) -> &Flag
-    {   &self.matrix[ x as usize ][ y as usize ]
-    }
-
-    //全体を埋める
-    fn fill_walls( &mut self )
-    {   self.matrix.iter_mut().for_each
-        (   |column| column.fill( Flag ( BIT_CELL_WALL ) )
-        );
-    }
+//新しいMapデータを作る
+pub fn make_new_data
+(   mut map: ResMut<Map>,
+    seed: Res<MazeSeed>,
+    o_level: Option<Res<LoadedMazeLevel>>,
+    levels: Res<Assets<MazeLevel>>,
+)
+{   //初期化する
+    map.reseed( seed.0 );
+    map.goal = None;
 
-    //指定の位置の地形を書き換える（フラグはクリアされる）
-    fn set_space( &mut self, cell: IVec2 )
-    {   if ! self.is_inside( cell ) { return }
-        *self.matrix_mut( cell ) = Flag ( BIT_CELL_SPACE );
+    //迷路ファイルがロードされていれば、迷路を作らずにそれを使う
+    match o_level.and_then( | handle | levels.get( &handle.0 ) )
+    {   Some ( level ) => level.install( &mut map ),
+        None => map.generate(),
     }
 
-    //指定の位置の地形にフラグを付加する
-    fn add_flag_deadend( &mut self, cell: IVec2 )
-    {   if ! self.is_inside( cell ) { return }
-        self.matrix_mut( cell ).0 |= BIT_FLAG_DEADEND;
+    //ゴールを決める（迷路ファイルで決まっていなければ）
+    if map.goal.is_none()
+    {   map.goal = Some ( map.choose_goal() );
     }
-}
-
-////////////////////////////////////////////////////////////////////////////////
 
-//Mapのpubメソッド
-impl Map
-{   //cellの四方を調べて空地がある方角のVecを返す
-    pub fn get_sides_space( &self, cell: IVec2 ) -> Vec< News >
-    {   //四方の空地を探し記録する
-        let mut sides = Vec::with_capacity( 4 );
-        for news in NEWS
-        {   if self.is_space( cell + news ) { sides.push( news ) }
+    //debug時は迷路の分析結果をログに出す
+    if misc::DEBUG()
+    {   for ( floor, stats ) in map.floor_stats().into_iter().enumerate()
+        {   info!( "Maze seed {} ({:?}) floor {}: {stats}", seed.0, map.algorithm, floor + 1 );
         }
-
-        sides //空地がある方角のVec
-    }
-
-    //指定の位置の地形・フラグを判定する
-    pub fn is_wall( &self, cell: IVec2 ) -> bool
-    {   if ! self.is_inside( cell ) { return true } //範囲外は壁にする
-        self.matrix( cell ).0 & BIT_CELL_WALL != 0
-    }
-    pub fn is_space( &self, cell: IVec2 ) -> bool
-    {   if ! self.is_inside( cell ) { return false } //範囲外に空地はない
-        self.matrix( cell ).0 & BIT_CELL_SPACE != 0
-    }
-    pub fn is_deadend( &self, cell: IVec2 ) -> bool
-    {   if ! self.is_inside( cell ) { return false } //範囲外に空地はない(＝行き止りもない)
-        self.matrix( cell ).0 & BIT_FLAG_DEADEND != 0
     }
 }
 
-////////////////////////////////////////////////////////////////////////////////
-
-//Mapのメソッド（迷路作成）
-impl Map
-{   //迷路作成メソッド
-    fn build_labyrinth( &mut self )
-    {   //穴を掘る準備
-        let mut cell = self.start;
-        let mut digable_walls = Vec::new();
-        let mut backtrack;
-
-        //穴掘りループ
-        loop
-        {   //四方の判定準備
-            digable_walls.clear();
-            backtrack = IVec2::NEG_ONE;
-
-            //四方の掘れる壁と戻り道を記録する
-            for news in NEWS
-            {   let next = cell + news;
-
-                //外壁は掘れない
-                if ! MAP_GRIDS_X_RANGE_INNER.contains( &next.x )
-                || ! MAP_GRIDS_Y_RANGE_INNER.contains( &next.y ) { continue }
-
-                //四方のグリッドを調べる
-                if self.is_wall( next ) && self.is_digable( next, news )
-                {   //壁であり且つ掘れるなら
-                    digable_walls.push( next );
-                }
-                else if self.is_space( next ) && ! self.is_deadend( next )
-                {   //道であり且つ行止りのマーキングがないなら
-                    backtrack = next;
-                }
-            }
-
-            if ! digable_walls.is_empty()
-            {   //掘れる壁が見つかったので、方向をランダムに決めて進む
-                cell = digable_walls[ self.rng.gen_range( 0..digable_walls.len() ) ];
-                self.set_space( cell );
-            }
-            else
-            {   //掘れる壁が見つからず、戻り道も見つからないなら迷路完成
-                if backtrack == IVec2::NEG_ONE { break }
-
-                //現在位置に行き止まりをマークし、戻り路へ進む(後戻りする)
-                self.add_flag_deadend( cell );
-                cell = backtrack;
-            }
-        }
-    }
+//Mapをテキスト形式でファイルに書き出す（バグ報告用）
+pub fn export_ascii
+(   map: Res<Map>,
+    seed: Res<MazeSeed>,
+    inkey: Res<Input<KeyCode>>,
+)
+{   if ! inkey.just_pressed( KeyCode::P ) { return } //[P]キー
 
-    //壁が掘れるか調べる
-    fn is_digable( &self, cell: IVec2, news: News ) -> bool
-    {    match news
-        {   News::North
-            if self.is_wall( cell + News::North + News::West )
-            && self.is_wall( cell + News::North              ) // 壁壁壁
-            && self.is_wall( cell + News::North + News::East ) // 壁？壁
-            && self.is_wall( cell + News::West               )
-                => true,
-            News::West
-            if self.is_wall( cell + News::North + News::West )
-            && self.is_wall( cell + News::North              ) // 壁壁
-            && self.is_wall( cell + News::West               ) // 壁？◎
-            && self.is_wall( cell + News::South + News::West ) // 壁壁
-            && self.is_wall( cell + News::South              )
-                => true,
-            News::East
-            if self.is_wall( cell + News::North              )
-            && self.is_wall( cell + News::North + News::East ) // 　壁壁
-            && self.is_wall( cell + News::East               ) // ◎？壁
-            && self.is_wall( cell + News::South              ) // 　壁壁
-            && self.is_wall( cell + News::South + News::East )
-                => true,
-            News::South
-            if self.is_wall( cell + News::West               )
-            && self.is_wall( cell + News::East               ) // 　◎
-            && self.is_wall( cell + News::South + News::West ) // 壁？壁
-            && self.is_wall( cell + News::South              ) // 壁壁壁
-            && self.is_wall( cell + News::South + News::East )
-                => true,
-            _   => false,
-        }
+    let path = format!( "{APP_TITLE}_{}.maze", seed.0 );
+    match std::fs::write( &path, map.to_ascii() )
+    {   Ok (()) => info!( "Exported maze to \"{path}\"" ),
+        Err ( err ) => warn!( "Failed to export maze \"{path}\": {err}" ),
     }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 
-//新しいMapデータを作る
-pub fn make_new_data( mut map: ResMut<Map> )
-{   //初期化する
-    map.fill_walls();
-
-    //スタート地点を決める
-    map.start = IVec2::new( MAP_GRIDS_WIDTH / 2, MAP_GRIDS_HEIGHT / 2 );
-    let start = map.start;
-    map.set_space( start );
-
-    //迷路を作る
-    map.build_labyrinth();
-}
-
-////////////////////////////////////////////////////////////////////////////////
-
 //Mapの全Entityの親になるEntityに印をつけるComponent
 #[derive( Component )]
 pub struct MapZeroEntity;
 
 //mapオブジェクト関係
-const WALL_CUBE_SIZE      : f32 = 1.0;             //壁のサイズ
+pub const WALL_CUBE_SIZE  : f32 = 1.0;             //壁のサイズ
 const WALL_CUBE_COLOR     : Color = Color::BISQUE; //通常Cubeの色
 const WALL_CUBE_COLOR_ZERO: Color = Color::RED;    //原点Cubeの色
 const GROUND_PLANE_COLOR  : Color = Color::MAROON; //地面の色
+const GOAL_RING_COLOR     : Color = Color::GOLD;   //ゴールの目印の色
+const GOAL_RING_RADIUS    : f32 = 0.3;             //ゴールの目印の半径
+
+const STAIRS_UP_COLOR     : Color = Color::CYAN;   //上り階段の目印の色
+const STAIRS_DOWN_COLOR   : Color = Color::TEAL;   //下り階段の目印の色
+const STAIRS_PLATE_SIZE   : f32 = 0.8;             //階段の目印の一辺
+
+const GOAL_RING_SPIN      : f32 = PI;              //ゴールの目印の回転速度(ラジアン/秒)
+
+//ゴールの目印のComponent
+#[derive( Component )]
+pub struct MapGoal;
+
+//各階の親になるEntityのComponent（中身は階）
+#[derive( Component )]
+pub struct MapFloor ( usize );
 
 //迷路の3Dオブジェクトをspawnする
+//各階は、その階の高さにある親Entityの子としてspawnする
 pub fn spawn_entity
 (   q_entity: Query<Entity, With<MapZeroEntity>>,
     map: Res<Map>,
@@ -236,50 +102,110 @@ pub fn spawn_entity
     let texture_wall_zero =
     (   if misc::DEBUG() { WALL_CUBE_COLOR_ZERO } else { WALL_CUBE_COLOR }
     ) .into();
-    let texture_wall_normal: StandardMaterial = WALL_CUBE_COLOR.into();
-    let texture_ground = GROUND_PLANE_COLOR.into();
+    let texture_wall_normal = materials.add( WALL_CUBE_COLOR.into() ); //全チャンクで共有する
+    let texture_ground = materials.add( GROUND_PLANE_COLOR.into() ); //全階で共有する
+    let stairs_mesh = meshes.add( shape::Box::new( STAIRS_PLATE_SIZE, 0.05, STAIRS_PLATE_SIZE ).into() );
+    let stairs_up   = materials.add( StandardMaterial { base_color: STAIRS_UP_COLOR  , emissive: STAIRS_UP_COLOR  , ..default() } );
+    let stairs_down = materials.add( StandardMaterial { base_color: STAIRS_DOWN_COLOR, emissive: STAIRS_DOWN_COLOR, ..default() } );
 
     //迷路をspawnする
+    let current = map.floor();
     cmds.spawn( ( PbrBundle::default(), MapZeroEntity ) ) //Cube(親)
     .insert( meshes.add( shape::Cube::new( size ).into() ) )
     .insert( Transform::from_translation( Vec3::ZERO ) ) //原点
     .insert( materials.add( texture_wall_zero ) )
     .with_children
     (   | cmds |
-        {   //子は、親からの相対位置にspawnされる(XZ平面)
-            for x in MAP_GRIDS_X_RANGE
-            {    for y in MAP_GRIDS_Y_RANGE
-                {   //原点は親なのでスキップ
-                    if x == 0 && y == 0 { continue }
-
-                    //3D空間の座標
-                    let grid = IVec2::new( x, y );
-                    let vec3 = grid.to_3dxz();
-
-                    //壁
-                    if map.is_wall( grid )
-                    {   cmds.spawn( PbrBundle::default() )
-                        .insert( meshes.add( shape::Cube::new( size ).into() ) )
-                        .insert( Transform::from_translation( vec3 ) )
-                        .insert( materials.add( texture_wall_normal.clone() ) )
+        {   for floor in 0..map.floor_count()
+            {   //今の階より上の階は隠しておく（show_floorsで切り替える）
+                let visibility = if floor <= current { Visibility::Inherited } else { Visibility::Hidden };
+                let transform = Transform::from_translation( IVec2::ZERO.to_3dxz_floor( floor ) );
+                cmds.spawn( ( SpatialBundle { visibility, transform, ..default() }, MapFloor ( floor ) ) )
+                .with_children
+                (   | cmds |
+                    {   //子は、階の親からの相対位置にspawnされる(XZ平面)
+                        //壁はチャンク単位で一つのメッシュにまとめる
+                        for ( x_range, y_range ) in map.wall_chunks()
+                        {   let Some ( mesh ) = map.wall_chunk_mesh( floor, x_range, y_range, size ) else { continue };
+                            cmds.spawn( PbrBundle::default() )
+                            .insert( meshes.add( mesh ) )
+                            .insert( texture_wall_normal.clone() )
+                            ;
+                        }
+
+                        //階段の目印も相対位置でspawnする（床に置いた光る板）
+                        for ( flag, material ) in [ ( Flag::StairsUp, &stairs_up ), ( Flag::StairsDown, &stairs_down ) ]
+                        {   for cell in map.cells_where_on( floor, move | c | c.flags.contains( flag ) )
+                            {   cmds.spawn( PbrBundle::default() )
+                                .insert( stairs_mesh.clone() )
+                                .insert( Transform::from_translation( cell.to_3dxz() ) )
+                                .insert( material.clone() )
+                                ;
+                            }
+                        }
+
+                        //ゴールの目印も相対位置でspawnする（立てたリングが光る）
+                        if let Some ( goal ) = map.goal.filter( | _ | floor == map.goal_floor )
+                        {   let color = GOAL_RING_COLOR;
+                            let ring = StandardMaterial { base_color: color, emissive: color, ..default() };
+                            let rotation = Quat::from_rotation_x( FRAC_PI_2 );
+                            let shape = shape::Torus
+                            {   radius     : GOAL_RING_RADIUS,
+                                ring_radius: GOAL_RING_RADIUS / 4.0,
+                                ..default()
+                            };
+                            cmds.spawn( ( PbrBundle::default(), MapGoal ) )
+                            .insert( meshes.add( shape.into() ) )
+                            .insert( Transform::from_translation( goal.to_3dxz() ).with_rotation( rotation ) )
+                            .insert( materials.add( ring ) )
+                            ;
+                        }
+
+                        //地面も相対位置でspawnする（縦横幅に合わせて拡大する）
+                        let width  = map.width()  as f32;
+                        let height = map.height() as f32;
+                        let position = Vec3::new( width - 1.0, 0.0, height - 1.0 ) / 2.0;
+                        let scale = Vec3::new( width, 1.0, height );
+                        cmds.spawn( PbrBundle::default() )
+                        .insert( meshes.add( shape::Plane::from_size( 1.0 ).into() ) )
+                        .insert( Transform::from_translation( position ).with_scale( scale ) )
+                        .insert( texture_ground.clone() )
                         ;
                     }
-                }
+                );
             }
+        }
+    );
+}
 
-            //地面も相対位置でspawnする
-            let long_side = MAP_GRIDS_WIDTH.max( MAP_GRIDS_HEIGHT ) as f32;
-            let half = long_side / 2.0;
-            let position = Vec3::new( half, 0.0, half ) - Vec3::ONE / 2.0;
-            cmds.spawn( PbrBundle::default() )
-            .insert( meshes.add( shape::Plane::from_size( long_side ).into() ) )
-            .insert( Transform::from_translation( position ) )
-            .insert( materials.add( texture_ground ) )
-            ;
+//今の階より上の階を隠す（三人称視点カメラで見下ろしても、今の階が見えるように）
+pub fn show_floors
+(   mut q_floor: Query<( &MapFloor, &mut Visibility )>,
+    map: Res<Map>,
+)
+{   if ! map.is_changed() { return }
+
+    q_floor.for_each_mut
+    (   | ( floor, mut visibility ) |
+        {   let is_visible = floor.0 <= map.floor();
+            if is_visible != ( *visibility != Visibility::Hidden )
+            {   *visibility = if is_visible { Visibility::Inherited } else { Visibility::Hidden };
+            }
         }
     );
 }
 
 ////////////////////////////////////////////////////////////////////////////////
 
+//ゴールの目印を回転させる
+pub fn spin_goal
+(   mut q_goal: Query<&mut Transform, With<MapGoal>>,
+    time: Res<Time>,
+)
+{   let angle = GOAL_RING_SPIN * time.delta().as_secs_f32();
+    q_goal.for_each_mut( | mut transform | transform.rotate_y( angle ) );
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
 //End of code.
\ No newline at end of file
@@ -0,0 +1,50 @@
+use super::*;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//手作りの迷路ファイル(.maze)のAsset
+//（ロード時に一度だけテキスト形式を読んでおくので、Mapへの読込みは失敗しない）
+#[derive( TypeUuid, TypePath )]
+#[uuid = "a68edccf-1503-4728-bb06-db70281d97b5"]
+pub struct MazeLevel { layout: MazeLayout }
+
+//迷路ファイルのAssetLoader
+#[derive( Default )]
+pub struct MazeLevelLoader;
+
+impl AssetLoader for MazeLevelLoader
+{   fn load<'a>
+    (   &'a self,
+        bytes: &'a [ u8 ],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>>
+    {   Box::pin
+        (   async move
+            {   //テキスト形式を読む（エラーは行と桁付きでログに出る。設定ファイル等は読まない）
+                let layout = MazeLayout::parse( std::str::from_utf8( bytes )? )?;
+
+                load_context.set_default_asset( LoadedAsset::new( MazeLevel { layout } ) );
+                Ok (())
+            }
+        )
+    }
+
+    fn extensions( &self ) -> &[ &str ] { &[ ASSETS_LEVEL_EXTENSION ] }
+}
+
+//ロードした迷路ファイルのハンドル（あればmake_new_dataが迷路を作らずにこれを使う）
+#[derive( Resource )]
+pub struct LoadedMazeLevel ( pub Handle<MazeLevel> );
+
+////////////////////////////////////////////////////////////////////////////////
+
+impl MazeLevel
+{   //ロードした迷路ファイルの地形とスタート・ゴールをMapに入れる
+    pub fn install( &self, map: &mut Map )
+    {   map.install_layout( &self.layout )
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
\ No newline at end of file
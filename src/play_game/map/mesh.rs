@@ -0,0 +1,100 @@
+use super::*;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//壁のメッシュを分割する単位（縦横のマス数）
+pub const WALL_CHUNK_GRIDS: i32 = 16;
+
+//Cubeの面の向き：法線、面の縦横の軸(u×v＝法線で反時計回りが表)、隣のマスの方角(上面はNone)
+const CUBE_FACES: [ ( Vec3, Vec3, Vec3, Option<News> ); 5 ] =
+[   ( Vec3::Y    , Vec3::Z, Vec3::X, None                ),
+    ( Vec3::X    , Vec3::Y, Vec3::Z, Some ( News::East  ) ),
+    ( Vec3::NEG_X, Vec3::Z, Vec3::Y, Some ( News::West  ) ),
+    ( Vec3::Z    , Vec3::X, Vec3::Y, Some ( News::South ) ),
+    ( Vec3::NEG_Z, Vec3::Y, Vec3::X, Some ( News::North ) ),
+];
+
+////////////////////////////////////////////////////////////////////////////////
+
+//Mapにメソッドを追加する準備（壁のメッシュ。Mapはライブラリの型なので、traitで追加する）
+pub trait WallMesh
+{   fn wall_chunk_mesh( &self, floor: usize, x_range: Range<i32>, y_range: Range<i32>, size: f32 ) -> Option<Mesh>;
+    fn wall_chunks( &self ) -> impl Iterator<Item = ( Range<i32>, Range<i32> )> + '_;
+}
+
+//Mapにメソッドを追加する
+impl WallMesh for Map
+{   //指定の階の指定の範囲の壁を一つのメッシュにまとめる（壁がなければNone）
+    //底面は作らない。壁同士が接する面も、隙間がなければ見えないので作らない
+    fn wall_chunk_mesh( &self, floor: usize, x_range: Range<i32>, y_range: Range<i32>, size: f32 ) -> Option<Mesh>
+    {   //一階の原点の壁は親のCubeなのでスキップ
+        let is_wall = | grid: IVec2 | self.is_inside( grid ) && self.is_wall_on( floor, grid );
+        let o_skip = ( floor == 0 ).then_some( IVec2::ZERO );
+        wall_mesh( is_wall, o_skip, x_range, y_range, size )
+    }
+
+    //マップ全体を分割する範囲を列挙する
+    fn wall_chunks( &self ) -> impl Iterator<Item = ( Range<i32>, Range<i32> )> + '_
+    {   let chunk = | range: Range<i32> |
+            range.clone().step_by( WALL_CHUNK_GRIDS as usize )
+            .map( move | i | i..( i + WALL_CHUNK_GRIDS ).min( range.end ) );
+        chunk( self.x_range() ).flat_map( move | xs | chunk( self.y_range() ).map( move | ys | ( xs.clone(), ys ) ) )
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//指定の範囲の壁を一つのメッシュにまとめる（壁がなければNone。o_skipのマスは作らない）
+//is_wallはマップの外ならfalseを返すこと（マップの外側の面は外から見えるので作る）
+pub fn wall_mesh
+(   is_wall: impl Fn( IVec2 ) -> bool,
+    o_skip: Option<IVec2>,
+    x_range: Range<i32>,
+    y_range: Range<i32>,
+    size: f32,
+) -> Option<Mesh>
+{   let is_gapless = size >= WALL_CUBE_SIZE;
+    let half = size / 2.0;
+
+    let mut positions: Vec<[ f32; 3 ]> = Vec::new();
+    let mut normals  : Vec<[ f32; 3 ]> = Vec::new();
+    let mut uvs      : Vec<[ f32; 2 ]> = Vec::new();
+    let mut indices  : Vec<u32> = Vec::new();
+
+    for x in x_range
+    {   for y in y_range.clone()
+        {   let grid = IVec2::new( x, y );
+            if o_skip == Some ( grid ) || ! is_wall( grid ) { continue }
+
+            let center = grid.to_3dxz();
+            for ( normal, u, v, o_news ) in CUBE_FACES
+            {   //隣が壁なら面は隠れる
+                if let Some ( news ) = o_news
+                {   if is_gapless && is_wall( grid + news ) { continue }
+                }
+
+                let base = positions.len() as u32;
+                let corners = [ -u - v, u - v, u + v, -u + v ];
+                for ( corner, uv ) in corners.iter().zip( [ [ 0.0, 1.0 ], [ 1.0, 1.0 ], [ 1.0, 0.0 ], [ 0.0, 0.0 ] ] )
+                {   positions.push( ( center + ( normal + *corner ) * half ).into() );
+                    normals.push( normal.into() );
+                    uvs.push( uv );
+                }
+                indices.extend( [ base, base + 1, base + 2, base, base + 2, base + 3 ] );
+            }
+        }
+    }
+    if positions.is_empty() { return None }
+
+    let mut mesh = Mesh::new( PrimitiveTopology::TriangleList );
+    mesh.insert_attribute( Mesh::ATTRIBUTE_POSITION, positions );
+    mesh.insert_attribute( Mesh::ATTRIBUTE_NORMAL  , normals   );
+    mesh.insert_attribute( Mesh::ATTRIBUTE_UV_0    , uvs       );
+    mesh.set_indices( Some ( Indices::U32( indices ) ) );
+
+    Some ( mesh )
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
\ No newline at end of file
@@ -0,0 +1,236 @@
+use super::*;
+
+//external crates
+use bevy::ecs::system::SystemParam;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//新しい迷路を作るEvent（seedがNoneなら乱数で決める）
+#[derive( Event, Clone, Copy, Default )]
+pub struct NewMaze { pub seed: Option<u64> }
+
+//ゲームメニューの状態のResource
+#[derive( Resource, Default )]
+pub struct GameMenu
+{   pub is_open: bool, //表示中か
+    cursor     : usize, //選択中の項目(MENU_ITEMSの添字)
+    seed_text  : String, //入力中のシード
+}
+
+//ゲームメニューのComponent
+#[derive( Component )]
+pub struct GameMenuUi;
+
+//メニューの項目
+#[derive( Clone, Copy, PartialEq )]
+enum MenuItem { NewMaze, GivenSeed, Resume }
+const MENU_ITEMS: [ MenuItem; 3 ] = [ MenuItem::NewMaze, MenuItem::GivenSeed, MenuItem::Resume ];
+
+//メニューの設定
+const SEED_TEXT_MAX: usize = 20; //u64の最大桁数
+const COLOR_TEXT_TITLE   : Color = Color::GOLD;
+const COLOR_TEXT_SELECTED: Color = Color::YELLOW;
+const COLOR_TEXT_NORMAL  : Color = Color::SILVER;
+
+//ゲームパッドのボタン入力（ゲームパッドは抜き挿しでIDが変わるので全てを調べる）
+#[derive( SystemParam )]
+pub struct GamepadInput<'w>
+{   gpdbtn  : Res<'w, Input<GamepadButton>>,
+    gamepads: Res<'w, Gamepads>,
+}
+
+impl GamepadInput<'_>
+{   fn just_pressed( &self, button_type: GamepadButtonType ) -> bool
+    {   self.gamepads.iter()
+        .any( | gamepad | self.gpdbtn.just_pressed( GamepadButton { gamepad, button_type } ) )
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//ゲームメニューが表示中か（run_if用）
+pub fn is_open( menu: Res<GameMenu> ) -> bool { menu.is_open }
+
+//[N]キーかゲームパッドの△ボタンで、すぐに新しい迷路を作る
+pub fn catch_new_maze_shortcut
+(   mut events: EventWriter<NewMaze>,
+    inkey: Res<Input<KeyCode>>,
+    gamepad: GamepadInput,
+)
+{   let is_gpdbtn_pressed = gamepad.just_pressed( GamepadButtonType::North ); //ps4[△]
+    if ! inkey.just_pressed( KeyCode::N ) && ! is_gpdbtn_pressed { return }
+
+    events.send( NewMaze::default() );
+}
+
+//[Tab]キーかゲームパッドのSTARTボタンでメニューを開閉する
+pub fn toggle
+(   mut cmds: Commands,
+    q_menu: Query<Entity, With<GameMenuUi>>,
+    mut menu: ResMut<GameMenu>,
+    seed: Res<MazeSeed>,
+    asset_svr: Res<AssetServer>,
+    inkey: Res<Input<KeyCode>>,
+    gamepad: GamepadInput,
+)
+{   let is_gpdbtn_pressed = gamepad.just_pressed( GamepadButtonType::Start ); //ps4[OPTIONS]
+    if ! inkey.just_pressed( KeyCode::Tab ) && ! is_gpdbtn_pressed { return }
+
+    if menu.is_open
+    {   q_menu.for_each( | id | cmds.entity( id ).despawn_recursive() );
+        *menu = GameMenu::default();
+    }
+    else
+    {   *menu = GameMenu { is_open: true, seed_text: seed.0.to_string(), ..default() };
+        spawn_ui( &mut cmds, &asset_svr );
+    }
+}
+
+//メニューを表示する（項目の文字列はupdate_uiで書く）
+fn spawn_ui( cmds: &mut Commands, asset_svr: &Res<AssetServer> )
+{   //3Dの表示エリア(viewport)の中央に縦に並べる
+    let style = Style
+    {   left           : Val::Px( SCREEN_FRAME.zero.x ),
+        top            : Val::Px( SCREEN_FRAME.zero.y ),
+        width          : Val::Px( SCREEN_FRAME.size.x ),
+        height         : Val::Px( SCREEN_FRAME.size.y ),
+        position_type  : PositionType::Absolute,
+        flex_direction : FlexDirection::Column,
+        justify_content: JustifyContent::Center,
+        align_items    : AlignItems::Center,
+        ..default()
+    };
+    let background_color = BackgroundColor ( Color::rgba( 0.0, 0.0, 0.0, 0.7 ) );
+    let frame = NodeBundle { style, background_color, ..default() };
+
+    let title = TextStyle
+    {   font     : asset_svr.load( ASSETS_FONT_ORBITRON_BLACK ),
+        font_size: PIXELS_PER_GRID * 1.5,
+        color    : COLOR_TEXT_TITLE,
+    };
+    let item = TextStyle
+    {   font     : asset_svr.load( ASSETS_FONT_PRESSSTART2P_REGULAR ),
+        font_size: PIXELS_PER_GRID * 0.6,
+        color    : COLOR_TEXT_NORMAL,
+    };
+    let sections = MENU_ITEMS.map( | _ | TextSection::new( "", item.clone() ) );
+
+    cmds.spawn( ( frame, GameMenuUi ) ).with_children
+    (   | cmds |
+        {   cmds.spawn( TextBundle::from_section( "MENU", title ) );
+            cmds.spawn( TextBundle::from_sections( sections ) );
+        }
+    );
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//メニュー表示中の入力
+//[↑↓]項目の選択、[Enter]決定、数字キー・[BackSpace]シードの入力
+pub fn catch_input
+(   mut cmds: Commands,
+    q_menu: Query<Entity, With<GameMenuUi>>,
+    mut menu: ResMut<GameMenu>,
+    mut events: EventWriter<NewMaze>,
+    inkey: Res<Input<KeyCode>>,
+    gamepad: GamepadInput,
+)
+{   //ゲームパッドは十字キーで選択、×ボタンで決定
+    let is_up     = inkey.just_pressed( KeyCode::Up     ) || gamepad.just_pressed( GamepadButtonType::DPadUp   );
+    let is_down   = inkey.just_pressed( KeyCode::Down   ) || gamepad.just_pressed( GamepadButtonType::DPadDown );
+    let is_decide = inkey.just_pressed( KeyCode::Return ) || gamepad.just_pressed( GamepadButtonType::South    );
+
+    if is_up   { menu.cursor = ( menu.cursor + MENU_ITEMS.len() - 1 ) % MENU_ITEMS.len() }
+    if is_down { menu.cursor = ( menu.cursor + 1 ) % MENU_ITEMS.len() }
+
+    //シードの入力
+    if MENU_ITEMS[ menu.cursor ] == MenuItem::GivenSeed
+    {   for keycode in inkey.get_just_pressed()
+        {   if let Some ( digit ) = keycode_to_digit( *keycode )
+            {   if menu.seed_text.len() < SEED_TEXT_MAX { menu.seed_text.push( digit ) }
+            }
+            else if *keycode == KeyCode::Back
+            {   menu.seed_text.pop();
+            }
+        }
+    }
+
+    if ! is_decide { return }
+
+    //決定した項目を実行してメニューを閉じる
+    match MENU_ITEMS[ menu.cursor ]
+    {   MenuItem::NewMaze => events.send( NewMaze::default() ),
+        MenuItem::GivenSeed =>
+        {   //u64に収まらない数字は受け付けない
+            let Ok ( seed ) = menu.seed_text.parse() else { return };
+            events.send( NewMaze { seed: Some ( seed ) } );
+        }
+        MenuItem::Resume => (),
+    }
+    q_menu.for_each( | id | cmds.entity( id ).despawn_recursive() );
+    *menu = GameMenu::default();
+}
+
+//数字キーを文字にする
+fn keycode_to_digit( keycode: KeyCode ) -> Option<char>
+{   let digit = match keycode
+    {   KeyCode::Key0 | KeyCode::Numpad0 => '0',
+        KeyCode::Key1 | KeyCode::Numpad1 => '1',
+        KeyCode::Key2 | KeyCode::Numpad2 => '2',
+        KeyCode::Key3 | KeyCode::Numpad3 => '3',
+        KeyCode::Key4 | KeyCode::Numpad4 => '4',
+        KeyCode::Key5 | KeyCode::Numpad5 => '5',
+        KeyCode::Key6 | KeyCode::Numpad6 => '6',
+        KeyCode::Key7 | KeyCode::Numpad7 => '7',
+        KeyCode::Key8 | KeyCode::Numpad8 => '8',
+        KeyCode::Key9 | KeyCode::Numpad9 => '9',
+        _ => return None,
+    };
+
+    Some ( digit )
+}
+
+//メニューの項目の表示を更新する
+pub fn update_ui
+(   mut q_text: Query<&mut Text, With<Parent>>,
+    q_menu: Query<&Children, With<GameMenuUi>>,
+    menu: Res<GameMenu>,
+)
+{   let Ok ( children ) = q_menu.get_single() else { return };
+    let Some ( &items ) = children.last() else { return };
+    let Ok ( mut text ) = q_text.get_mut( items ) else { return };
+
+    for ( i, ( section, item ) ) in text.sections.iter_mut().zip( MENU_ITEMS ).enumerate()
+    {   let is_selected = i == menu.cursor;
+        let label = match item
+        {   MenuItem::NewMaze   => "NEW MAZE".to_string(),
+            MenuItem::GivenSeed => format!( "SEED {}{}", menu.seed_text, if is_selected { "_" } else { "" } ),
+            MenuItem::Resume    => "RESUME".to_string(),
+        };
+        section.value = format!( "{}{label}\n", if is_selected { "> " } else { "  " } );
+        section.style.color = if is_selected { COLOR_TEXT_SELECTED } else { COLOR_TEXT_NORMAL };
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//新しい迷路を作る
+//（GameStartを経由してMainLoopへ入り直すと、OnEnterの処理で迷路・Player・カメラが全て作り直される）
+pub fn start_new_maze
+(   mut cmds: Commands,
+    mut events: EventReader<NewMaze>,
+    mut seed: ResMut<MazeSeed>,
+    mut progress: ResMut<Progress>,
+    mut next_state: ResMut<NextState<MyState>>,
+)
+{   let Some ( new_maze ) = events.iter().last() else { return };
+
+    seed.0 = new_maze.seed.unwrap_or_else( random );
+    *progress = Progress::default(); //レベル1からやり直す
+    cmds.remove_resource::<LoadedMazeLevel>(); //迷路ファイルではなく新しく作る
+    next_state.set( MyState::GameStart );
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
@@ -0,0 +1,142 @@
+use super::*;
+
+//external crates
+use bevy::render::render_resource::{ Extent3d, TextureDimension, TextureFormat };
+
+////////////////////////////////////////////////////////////////////////////////
+
+//ミニマップのComponent
+#[derive( Component )]
+pub struct Minimap;
+
+//ミニマップの設定
+const MINIMAP_CELLS: i32 = 21;           //Playerを中心に表示するマス数(縦横・奇数)
+const MINIMAP_PIXELS_PER_CELL: i32 = 5;  //1マスのテクスチャ上のPixel数(矢印を描くので奇数)
+pub const COLOR_UNKNOWN: [ u8; 4 ] = [  34,  34,  46, 255 ]; //未探索
+pub const COLOR_SPACE  : [ u8; 4 ] = [ 160, 140, 120, 255 ]; //見えた通路
+pub const COLOR_WALL   : [ u8; 4 ] = [  90,  30,  30, 255 ]; //見えた壁
+pub const COLOR_VISITED: [ u8; 4 ] = [ 220, 200, 170, 255 ]; //通った通路
+pub const COLOR_GOAL   : [ u8; 4 ] = [ 255, 215,   0, 255 ]; //ゴール
+pub const COLOR_STAIRS : [ u8; 4 ] = [   0, 200, 200, 255 ]; //見えた階段
+pub const COLOR_PLAYER : [ u8; 4 ] = [ 255, 255,   0, 255 ]; //Playerの矢印
+
+//Playerの矢印（北向き。他の向きは回転して描く）
+const ARROW: [ &str; MINIMAP_PIXELS_PER_CELL as usize ] =
+[   "..#..",
+    ".###.",
+    "#####",
+    "..#..",
+    "..#..",
+];
+
+////////////////////////////////////////////////////////////////////////////////
+
+//ミニマップのスプライトをspawnする（テクスチャは空で作り、update_imageで描く）
+pub fn spawn_sprite
+(   q_minimap: Query<Entity, With<Minimap>>,
+    mut cmds: Commands,
+    mut images: ResMut<Assets<Image>>,
+)
+{   //既存のミニマップがあれば削除する
+    q_minimap.for_each( | id | cmds.entity( id ).despawn_recursive() );
+
+    let pixels = ( MINIMAP_CELLS * MINIMAP_PIXELS_PER_CELL ) as u32;
+    let size = Extent3d { width: pixels, height: pixels, depth_or_array_layers: 1 };
+    let image = Image::new_fill
+    (   size,
+        TextureDimension::D2,
+        &COLOR_UNKNOWN,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+
+    //表示エリアの中央に置く
+    let custom_size = Some ( MINIMAP_GRIDS_SIZE.as_vec2() * PIXELS_PER_GRID );
+    let offset = ( MINIMAP_GRIDS_SIZE - 1 ).as_vec2() * Vec2::new( 1.0, -1.0 ) * PIXELS_PER_GRID / 2.0;
+    let vec2 = MINIMAP_GRIDS_ZERO.to_screen_pixel() + offset;
+    let vec3 = vec2.extend( DEPTH_SPRITE_MINIMAP );
+
+    cmds.spawn( ( SpriteBundle::default(), Minimap ) )
+    .insert( Sprite { custom_size, ..default() } )
+    .insert( Transform::from_translation( vec3 ) )
+    .insert( images.add( image ) )
+    ;
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//Playerが一歩進むか向きを変え終えたら、ミニマップを描き直す
+pub fn update_image
+(   q_player: Query<Ref<player::Player>>,
+    q_minimap: Query<&Handle<Image>, With<Minimap>>,
+    map: Res<map::Map>,
+    mut images: ResMut<Assets<Image>>,
+)
+{   let Ok ( player ) = q_player.get_single() else { return };
+    if ! player.is_changed() || ! player.is_stop() { return }
+    let Ok ( handle ) = q_minimap.get_single() else { return };
+    let Some ( image ) = images.get_mut( handle ) else { return };
+
+    //Playerを中心にマスを塗る
+    let half = MINIMAP_CELLS / 2;
+    let center = player.position();
+    for y in 0..MINIMAP_CELLS
+    {   for x in 0..MINIMAP_CELLS
+        {   let cell = center + IVec2::new( x - half, y - half );
+            let zero = IVec2::new( x, y ) * MINIMAP_PIXELS_PER_CELL;
+            paint( image, zero, MINIMAP_PIXELS_PER_CELL, | _ | true, cell_color( &map, cell ) );
+        }
+    }
+
+    //中心にPlayerの向きの矢印を描く
+    let zero = IVec2::splat( half * MINIMAP_PIXELS_PER_CELL );
+    let is_arrow = arrow( player.direction(), MINIMAP_PIXELS_PER_CELL );
+    paint( image, zero, MINIMAP_PIXELS_PER_CELL, is_arrow, COLOR_PLAYER );
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//今の階のマスの色（見えていないマスは未探索の色。ゴールの階ならゴールは常に表示する）
+pub fn cell_color( map: &map::Map, cell: IVec2 ) -> [ u8; 4 ]
+{   if Some ( cell ) == map.goal && map.floor() == map.goal_floor { return COLOR_GOAL }
+    if ! map.has_flag( cell, map::Flag::Seen ) { return COLOR_UNKNOWN }
+
+    let flags = map.flags( cell );
+    if flags.contains( map::Flag::StairsUp ) || flags.contains( map::Flag::StairsDown ) { COLOR_STAIRS }
+    else if map.has_flag( cell, map::Flag::Visited ) { COLOR_VISITED }
+    else if map.is_space( cell ) { COLOR_SPACE } else { COLOR_WALL }
+}
+
+//指定の向きの矢印の形（sizeはPixel数。北向きの図案を回転・拡大する）
+pub fn arrow( direction: News, size: i32 ) -> impl Fn( IVec2 ) -> bool
+{   move | IVec2 { x, y } |
+    {   let last = size - 1;
+        let ( x, y ) = match direction
+        {   News::North => ( x, y ),
+            News::East  => ( y, last - x ),
+            News::South => ( last - x, last - y ),
+            News::West  => ( last - y, x ),
+        };
+        let scale = | n | ( n * ARROW.len() as i32 / size ) as usize;
+        ARROW[ scale( y ) ].as_bytes()[ scale( x ) ] == b'#'
+    }
+}
+
+//テクスチャの正方形の範囲(左上zero、一辺size)のうち、条件に合うPixelを塗る（はみ出す部分は塗らない）
+pub fn paint( image: &mut Image, zero: IVec2, size: i32, filter: impl Fn( IVec2 ) -> bool, color: [ u8; 4 ] )
+{   let width  = image.texture_descriptor.size.width  as i32;
+    let height = image.texture_descriptor.size.height as i32;
+    for dy in 0..size
+    {   for dx in 0..size
+        {   let pixel = zero + IVec2::new( dx, dy );
+            if pixel.x < 0 || pixel.x >= width || pixel.y < 0 || pixel.y >= height { continue }
+            if ! filter( IVec2::new( dx, dy ) ) { continue }
+
+            let index = ( ( pixel.y * width + pixel.x ) * 4 ) as usize;
+            image.data[ index..index + 4 ].copy_from_slice( &color );
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
@@ -13,6 +13,7 @@ use super::*;
 #[derive( Component, Default )]
 pub struct Player
 {   position : IVec2,    //位置
+    floor    : usize,    //階
     direction: News,     //向き
     in_action: InAction, //行動の種類
 }
@@ -22,10 +23,14 @@ enum InAction
 {   #[default] Stop,
     TurnRight, TurnLeft, //左右回転
     Forward, Backward,   //前進後退
+    Climb, Descend,      //階段の上り下り
 }
 
 impl Player
-{   fn is_stop( &self ) -> bool
+{   pub fn position ( &self ) -> IVec2 { self.position  }
+    pub fn direction( &self ) -> News  { self.direction }
+
+    pub fn is_stop( &self ) -> bool
     {   self.in_action == InAction::Stop
     }
     fn is_turn( &self ) -> bool
@@ -34,6 +39,25 @@ impl Player
     fn is_move( &self ) -> bool
     {   self.in_action == InAction::Forward || self.in_action == InAction::Backward
     }
+    fn is_climb( &self ) -> bool
+    {   self.in_action == InAction::Climb || self.in_action == InAction::Descend
+    }
+
+    //階段を上り下りする（足元に階段がなければfalse）
+    fn climb( &mut self, map: &mut map::Map, is_up: bool ) -> bool
+    {   let ( flag, in_action ) = if is_up
+        {   ( map::Flag::StairsUp, InAction::Climb )
+        }
+        else
+        {   ( map::Flag::StairsDown, InAction::Descend )
+        };
+        if ! map.has_flag( self.position, flag ) { return false }
+
+        self.floor = if is_up { self.floor + 1 } else { self.floor - 1 };
+        self.in_action = in_action;
+        map.set_floor( self.floor );
+        true
+    }
 }
 
 //Player Figure のComponent
@@ -100,7 +124,7 @@ impl<T: Component> SpawnTrait2<T> for &mut ChildBuilder<'_, '_, '_>
 //Playerの3Dオブジェクトをspawnする
 pub fn spawn_entity
 (   que_player: Query<Entity, With<Player>>,
-    map: Res<map::Map>,
+    mut map: ResMut<map::Map>,
     mut orbit_camera: ResMut<OrbitCamera>,
     mut cmds: Commands,
     meshes: ResMut<Assets<Mesh>>,
@@ -109,13 +133,17 @@ pub fn spawn_entity
 {   //既存のPlayerがあれば削除する
     que_player.for_each( | id | cmds.entity( id ).despawn_recursive() );
 
-    //Playerの設定
+    //Playerの設定（向きもMapの乱数で決めて、シードから再現できるようにする）
     let sides = map.get_sides_space( map.start );
-    let side = sides[ rand::thread_rng().gen_range( 0..sides.len() ) ];
+    let side = sides[ map.rng().gen_range( 0..sides.len() ) ];
     let direction = side;
-    let player = Player { position: map.start, direction, ..default() };
+    let player = Player { position: map.start, floor: map.floor(), direction, ..default() };
 
-    let player_position  = player.position.to_3dxz();
+    //スタート地点からの視界を記録する
+    let start = map.start;
+    map.update_fog( start );
+
+    let player_position  = player.position.to_3dxz_floor( player.floor );
     let player_direction = player.direction.to_quat();
 
     //一人称視点カメラの設定
@@ -161,9 +189,10 @@ pub fn spawn_entity
 //キー入力によって自機の位置と向きを更新する
 pub fn catch_input_keyboard
 (   mut que_player: Query<&mut Player>,
-    map: Res<map::Map>,
+    mut map: ResMut<map::Map>,
     orbit_camera: Res<OrbitCamera>,
     inkey: Res<Input<KeyCode>>,
+    mut record: ResMut<StageRecord>,
 )
 {   //三人称視点カメラがアクティブなら、入力を受け付けない
     if orbit_camera.is_active { return }
@@ -172,7 +201,7 @@ pub fn catch_input_keyboard
     let Ok ( mut player ) = que_player.get_single_mut() else { return };
     if ! player.is_stop() { return }
 
-    //自機の位置と向きを更新する
+    //自機の位置と向きを更新する（同時に押されたキーは、最初に動作が決まったものだけ受け付ける）
     for keycode in inkey.get_just_pressed()
     {   match keycode
         {   KeyCode::Right =>
@@ -188,6 +217,7 @@ pub fn catch_input_keyboard
                 if map.is_space( player.position + front )
                 {   player.position += front;
                     player.in_action = InAction::Forward;
+                    record.steps += 1;
                 }
             }
             KeyCode::Down =>
@@ -195,11 +225,21 @@ pub fn catch_input_keyboard
                 if map.is_space( player.position + back )
                 {   player.position += back;
                     player.in_action = InAction::Backward;
+                    record.steps += 1;
                 }
             }
+            KeyCode::PageUp | KeyCode::PageDown =>
+            {   //足元の階段を上り下りする
+                let is_up = *keycode == KeyCode::PageUp;
+                if player.climb( &mut map, is_up ) { record.steps += 1 }
+            }
             _ => (),
         }
+        if ! player.is_stop() { break }
     }
+
+    //移動したら、移動先からの視界を記録する（階段なら移動先の階の視界）
+    if player.is_move() || player.is_climb() { map.update_fog( player.position ) }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -240,7 +280,7 @@ pub fn rotate_player
     }
 }
 
-//プレイヤーを前進後退させる
+//プレイヤーを前進後退させる（階段なら上下に移動させる）
 pub fn move_player
 (   mut que_player: Query<(&mut Transform, &mut Player)>,
     time: Res<Time>,
@@ -248,23 +288,24 @@ pub fn move_player
 )
 {   let Ok ( ( mut transform, mut player ) ) = que_player.get_single_mut() else { return };
 
-    if ! player.is_move() { return } //前進後退でないなら
+    if ! player.is_move() && ! player.is_climb() { return } //前進後退でも上り下りでもないなら
 
-    //微小時間の移動距離
-    let delta = UNIT_MOVE * time.delta().as_secs_f32() * PLAYER_MOVE_COEF;
+    //微小時間の移動距離（上り下りは一階分の高さを同じ時間で移動する）
+    let unit = if player.is_climb() { MAP_FLOOR_HEIGHT } else { UNIT_MOVE };
+    let delta = unit * time.delta().as_secs_f32() * PLAYER_MOVE_COEF;
     *distance += delta; //累積を保存する
 
     //累積が1単位を超えたら
-    if *distance >= UNIT_MOVE
+    if *distance >= unit
     {   //位置をピッタリにする
-        *transform = Transform::from_translation( player.position.to_3dxz() );
+        *transform = Transform::from_translation( player.position.to_3dxz_floor( player.floor ) );
 
         //情報更新する
         player.in_action = InAction::Stop;
         *distance = 0.0;
     }
     else
-    {   //前進後退する（中間アニメーション）
+    {   //前進後退か上り下りする（中間アニメーション）
         let vec3 = delta * match player.direction
         {   News::North => Vec3::NEG_Z,
             News::East  => Vec3::X,
@@ -275,6 +316,8 @@ pub fn move_player
         match player.in_action
         {   InAction::Forward  => transform.translation += vec3,
             InAction::Backward => transform.translation -= vec3,
+            InAction::Climb    => transform.translation.y += delta,
+            InAction::Descend  => transform.translation.y -= delta,
             _ => (),
         }
     }
@@ -282,4 +325,20 @@ pub fn move_player
 
 ////////////////////////////////////////////////////////////////////////////////
 
+//プレイヤーがゴールに着いたらステージクリアへ遷移する
+pub fn check_goal
+(   que_player: Query<&Player>,
+    map: Res<map::Map>,
+    mut next_state: ResMut<NextState<MyState>>,
+)
+{   let Ok ( player ) = que_player.get_single() else { return };
+
+    //移動のアニメーションが終わってから判定する（ゴールの階にいること）
+    if ! player.is_stop() || Some ( player.position ) != map.goal || player.floor != map.goal_floor { return }
+
+    next_state.set( MyState::StageClear );
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
 //End of code.
\ No newline at end of file
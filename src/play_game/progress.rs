@@ -0,0 +1,143 @@
+use super::*;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//ゲームモードのResource
+#[derive( Resource, Clone, Copy, PartialEq, Eq, Debug )]
+pub enum GameMode
+{   Normal,  //迷路の大きさとアルゴリズムは設定のまま
+    Endless, //レベルが上がるごとに迷路が大きく・難しくなる
+}
+
+impl Default for GameMode
+{   fn default() -> Self
+    {   //コマンドライン引数か設定ファイルで指定できる（例：--mode endless）
+        match misc::setting( "mode" ).as_deref()
+        {   Some ( "endless" ) => GameMode::Endless,
+            Some ( "normal" ) | None => GameMode::Normal,
+            Some ( mode ) =>
+            {   warn!( "Unknown game mode \"{mode}\"" );
+                GameMode::Normal
+            }
+        }
+    }
+}
+
+//エンドレスモードの設定
+const ENDLESS_SIZE_START: i32 = 11; //レベル1の縦横幅(奇数)
+const ENDLESS_SIZE_STEP : i32 = 4;  //レベルごとに広げる幅(偶数にして奇数を保つ)
+
+//エンドレスモードのブレイド率（レベルごとに減らし、ループを減らして行き止りを増やす）
+//同じ広さなら、ブレイド率が低いほど難易度の点数が高い（59x59の穴掘り法で、0.6なら50点前後、0.0なら160点前後）
+const ENDLESS_BRAID_START: f32 = 0.5; //レベル1のブレイド率
+const ENDLESS_BRAID_STEP : f32 = 0.1; //レベルごとに減らすブレイド率(レベル6から完全迷路)
+
+//エンドレスモードの難易度の点数の下限（レベル×この値。届かなければシードを変えて作り直す）
+//どのアルゴリズムでもレベルの広さの平均点を下回るようにして、易しすぎる迷路だけを除く
+const ENDLESS_DIFFICULTY_STEP: f32 = 3.0;
+const ENDLESS_RETRIES: usize = 8; //作り直す回数の上限
+
+//エンドレスモードのアルゴリズムの順番（レベル1から順に使い、最後のものを繰り返す）
+//23x23の完全迷路の平均点の低い順：Prim 16、HuntAndKill 20、Kruskal・Wilson・Eller・Sidewinder・
+//RecursiveDivision 24～27、穴掘り法 35（Kruskal以降の5つはほぼ同じなので、見た目が変わるように並べた）
+const ENDLESS_ALGORITHMS: [ map::Algorithm; 8 ] =
+[   map::Algorithm::Prim,
+    map::Algorithm::HuntAndKill,
+    map::Algorithm::Kruskal,
+    map::Algorithm::Wilson,
+    map::Algorithm::Eller,
+    map::Algorithm::Sidewinder,
+    map::Algorithm::RecursiveDivision,
+    map::Algorithm::Digger,
+];
+
+//スコアの設定
+const SCORE_PER_STEP: u32 = 10; //最短経路の一歩あたりの点数
+
+////////////////////////////////////////////////////////////////////////////////
+
+//エンドレスモードなら、レベルに合わせて迷路の大きさとアルゴリズムとブレイド率を決める
+pub fn apply_level
+(   mut map: ResMut<map::Map>,
+    mode: Res<GameMode>,
+    progress: Res<Progress>,
+)
+{   if *mode != GameMode::Endless { return }
+    set_level( &mut map, progress.level );
+}
+
+//レベルに合わせて迷路の大きさとアルゴリズムとブレイド率を決める
+pub fn set_level( map: &mut map::Map, level: u32 )
+{   let level = level.max( 1 ) as usize - 1;
+    let size = ENDLESS_SIZE_STEP.saturating_mul( level as i32 ).saturating_add( ENDLESS_SIZE_START );
+    map.resize( IVec2::splat( size ) ); //上限はresizeで抑えられる
+    map.algorithm = ENDLESS_ALGORITHMS[ level.min( ENDLESS_ALGORITHMS.len() - 1 ) ];
+    map.braid = ( ENDLESS_BRAID_START - ENDLESS_BRAID_STEP * level as f32 ).max( 0.0 );
+}
+
+//レベルの難易度の点数の下限
+pub fn difficulty_target( level: u32 ) -> f32
+{   ENDLESS_DIFFICULTY_STEP * level as f32
+}
+
+//エンドレスモードで、難易度の点数が下限に届かない迷路ならシードを変えて作り直す
+//（作り直したシードを表示して、そのシードから同じ迷路を作れるようにする）
+pub fn reject_easy_maze
+(   mut map: ResMut<map::Map>,
+    mut seed: ResMut<MazeSeed>,
+    mode: Res<GameMode>,
+    progress: Res<Progress>,
+    o_level: Option<Res<map::LoadedMazeLevel>>,
+)
+{   if *mode != GameMode::Endless || o_level.is_some() { return } //迷路ファイルはそのまま使う
+    if let Some ( new_seed ) = regenerate_until( &mut map, difficulty_target( progress.level ) )
+    {   seed.0 = new_seed;
+    }
+}
+
+//難易度の点数が下限に届くまで、シードを変えて迷路を作り直す（作り直したら最後のシードを返す）
+pub fn regenerate_until( map: &mut map::Map, target: f32 ) -> Option<u64>
+{   let mut o_seed = None;
+    for _ in 0..ENDLESS_RETRIES
+    {   if map.difficulty() >= target { return o_seed }
+        let seed = map.rng().gen();
+        map.reseed( seed );
+        map.generate();
+        o_seed = Some ( seed );
+    }
+    if map.difficulty() < target { warn!( "Maze difficulty {:.1} is below {target:.1}", map.difficulty() ) }
+    o_seed
+}
+
+//エンドレスモードなら、次の迷路のレベルを上げる
+//（通常モードは迷路が変わらないので、レベルも得点の倍率も1のまま）
+pub fn level_up
+(   mut progress: ResMut<Progress>,
+    mode: Res<GameMode>,
+)
+{   if *mode == GameMode::Endless { progress.level += 1 }
+}
+
+//ステージの得点を計算して累計に加える
+//（最短経路の歩数×点数×レベル。遠回りした分だけ減る。複数階なら階段の上り下りも一歩）
+pub fn add_stage_score
+(   mut progress: ResMut<Progress>,
+    mut record: ResMut<StageRecord>,
+    map: Res<map::Map>,
+)
+{   let par = map.solution_length().unwrap_or( 0 );
+    record.score = stage_score( par, record.steps, progress.level );
+    progress.score = progress.score.saturating_add( record.score );
+}
+
+//ステージの得点（広い迷路や高いレベルでも桁あふれしないようu64で計算し、u32の上限で止める）
+pub fn stage_score( par: usize, steps: u32, level: u32 ) -> u32
+{   let par = par as u64;
+    let steps = ( steps as u64 ).max( par ).max( 1 );
+    let score = ( par * par ).saturating_mul( SCORE_PER_STEP as u64 ) / steps;
+    u32::try_from( score.saturating_mul( level as u64 ) ).unwrap_or( u32::MAX )
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
@@ -0,0 +1,93 @@
+use super::*;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//結果画面のComponent
+#[derive( Component )]
+pub struct StageClearUi;
+
+//結果画面の設定
+const STAGE_CLEAR_WAIT: f32 = 5.0; //次の迷路へ自動で進むまでの時間(秒)
+const COLOR_TEXT_TITLE: Color = Color::GOLD;
+const COLOR_TEXT_BODY : Color = Color::SILVER;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//結果画面を表示する
+pub fn spawn_results
+(   mut cmds: Commands,
+    asset_svr: Res<AssetServer>,
+    record: Res<StageRecord>,
+    progress: Res<Progress>,
+    map: Res<map::Map>,
+)
+{   //3Dの表示エリア(viewport)の中央に縦に並べる
+    let style = Style
+    {   left           : Val::Px( SCREEN_FRAME.zero.x ),
+        top            : Val::Px( SCREEN_FRAME.zero.y ),
+        width          : Val::Px( SCREEN_FRAME.size.x ),
+        height         : Val::Px( SCREEN_FRAME.size.y ),
+        position_type  : PositionType::Absolute,
+        flex_direction : FlexDirection::Column,
+        justify_content: JustifyContent::Center,
+        align_items    : AlignItems::Center,
+        ..default()
+    };
+    let background_color = BackgroundColor ( Color::rgba( 0.0, 0.0, 0.0, 0.5 ) );
+    let frame = NodeBundle { style, background_color, ..default() };
+
+    //表示する文字列
+    let minutes = ( record.time / 60.0 ) as u32;
+    let seconds = record.time % 60.0;
+    let lines =
+    [   ( "STAGE CLEAR".to_string(), ASSETS_FONT_ORBITRON_BLACK, 1.5, COLOR_TEXT_TITLE ),
+        ( format!( "STEPS {}", record.steps ), ASSETS_FONT_PRESSSTART2P_REGULAR, 0.6, COLOR_TEXT_BODY ),
+        ( format!( "TIME {minutes:02}:{seconds:05.2}" ), ASSETS_FONT_PRESSSTART2P_REGULAR, 0.6, COLOR_TEXT_BODY ),
+        ( format!( "EXPLORED {:.0}%", map.explored_ratio() * 100.0 ), ASSETS_FONT_PRESSSTART2P_REGULAR, 0.6, COLOR_TEXT_BODY ),
+        ( format!( "SCORE {} (TOTAL {})", record.score, progress.score ), ASSETS_FONT_PRESSSTART2P_REGULAR, 0.6, COLOR_TEXT_BODY ),
+        ( "PRESS ENTER".to_string(), ASSETS_FONT_PRESSSTART2P_REGULAR, 0.4, COLOR_TEXT_BODY ),
+    ];
+
+    cmds.spawn( ( frame, StageClearUi ) ).with_children
+    (   | cmds |
+        for ( value, file, size, color ) in lines
+        {   let style = TextStyle
+            {   font     : asset_svr.load( file ),
+                font_size: PIXELS_PER_GRID * size,
+                color,
+            };
+            cmds.spawn( TextBundle::from_section( value, style ) );
+        }
+    );
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//[Enter]キーか一定時間の経過で次の迷路へ進む
+pub fn goto_next_maze
+(   mut map: ResMut<map::Map>,
+    mut seed: ResMut<MazeSeed>,
+    mut next_state: ResMut<NextState<MyState>>,
+    inkey: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut timer: Local<f32>,
+)
+{   *timer += time.delta().as_secs_f32();
+    if ! inkey.just_pressed( KeyCode::Return ) && *timer < STAGE_CLEAR_WAIT { return }
+    *timer = 0.0;
+
+    //次の迷路のシードは今の迷路の乱数から決める（最初のシードから再現できる）
+    seed.0 = map.rng().gen();
+
+    //GameStartを経由してMainLoopへ入ると、新しい迷路が作られる
+    next_state.set( MyState::GameStart );
+}
+
+//ロードした迷路ファイルを忘れる（次の迷路からは新しく作る）
+pub fn forget_loaded_level( mut cmds: Commands )
+{   cmds.remove_resource::<LoadedMazeLevel>();
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
\ No newline at end of file
@@ -0,0 +1,377 @@
+use super::*;
+
+//external crates
+use bevy::time::TimeUpdateStrategy;
+
+//standard library
+use std::time::Duration;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//テスト用の迷路（Sは(1,1)。北と西は壁、東と南は通路）
+const TEST_MAZE: &str = "\
+#######
+#S....#
+#.###.#
+#.#G..#
+#######
+";
+
+//二階建てのテスト用の迷路（(4,1)の上り階段の真上が下り階段。ゴールは二階）
+const TEST_MAZE_FLOORS: &str = "\
+#######
+#S..<.#
+#.###.#
+#.....#
+#######
+
+#######
+#...>.#
+#.###.#
+#..G..#
+#######
+";
+
+//1フレームの経過時間と、アニメーションを待つフレーム数の上限
+const FRAME_TIME: Duration = Duration::from_millis( 16 );
+const FRAMES_MAX: usize = 600;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//GPUもウィンドウも使わずに、ゲームプレイのシステムを動かすテスト用のApp
+struct Harness ( App );
+
+impl Harness
+{   //固定の迷路にPlayerとカメラをspawnしたAppを作る
+    fn new( maze: &str ) -> Self
+    {   let mut map = map::Map::new( IVec2::splat( MAP_GRIDS_MIN ), default() );
+        map.load_ascii( maze ).expect( "test maze must be valid" );
+
+        let mut app = App::new();
+        app
+        .add_plugins( MinimalPlugins )
+        .add_plugins( AssetPlugin::default() )
+        .add_asset::<Mesh>()
+        .add_asset::<StandardMaterial>()
+        .insert_resource( TimeUpdateStrategy::ManualDuration( FRAME_TIME ) ) //時間を一定に進める
+        .init_resource::<Input<KeyCode>>()
+        .init_resource::<OrbitCamera>()
+        .init_resource::<StageRecord>()
+        .insert_resource( map )
+        .add_systems( Startup, player::spawn_entity )
+        .add_systems
+        (   Update,
+            (   switch_fpp_and_tpp,
+                player::catch_input_keyboard,
+                (   player::rotate_player,
+                    player::move_player,
+                ),
+            )
+            .chain()
+        );
+        app.update(); //Startup
+
+        Self ( app )
+    }
+
+    //キーを押して1フレーム進め、キーを離す
+    fn press( &mut self, keycode: KeyCode )
+    {   self.press_together( &[ keycode ] );
+    }
+
+    //複数のキーを同じフレームで押して1フレーム進め、キーを離す
+    fn press_together( &mut self, keycodes: &[ KeyCode ] )
+    {   let mut inkey = self.0.world.resource_mut::<Input<KeyCode>>();
+        keycodes.iter().for_each( | &keycode | inkey.press( keycode ) );
+        self.0.update();
+
+        let mut inkey = self.0.world.resource_mut::<Input<KeyCode>>();
+        inkey.release_all();
+        inkey.clear();
+    }
+
+    //Playerのアニメーションが終わるまでフレームを進める
+    fn run_until_stop( &mut self )
+    {   for _ in 0..FRAMES_MAX
+        {   self.0.update();
+            if self.player().is_stop() { return }
+        }
+        panic!( "player did not stop within {FRAMES_MAX} frames" );
+    }
+
+    //キーを押して、アニメーションが終わるまで待つ
+    fn step( &mut self, keycode: KeyCode )
+    {   self.press( keycode );
+        self.run_until_stop();
+    }
+
+    //Playerを向きたい方角へ右旋回させる
+    fn face( &mut self, news: News )
+    {   for _ in NEWS
+        {   if self.player().direction() == news { return }
+            self.step( KeyCode::Right );
+        }
+        panic!( "player could not face {news:?}" );
+    }
+
+    fn player( &mut self ) -> &player::Player
+    {   self.0.world.query::<&player::Player>().single( &self.0.world )
+    }
+
+    fn player_translation( &mut self ) -> Vec3
+    {   self.0.world.query_filtered::<&Transform, With<player::Player>>().single( &self.0.world ).translation
+    }
+
+    fn camera_is_active<T: Component>( &mut self ) -> bool
+    {   self.0.world.query_filtered::<&Camera, With<T>>().single( &self.0.world ).is_active
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+#[test]
+fn player_spawns_on_start()
+{   let mut harness = Harness::new( TEST_MAZE );
+    let start = IVec2::new( 1, 1 );
+
+    assert_eq!( harness.player().position(), start );
+    assert!( [ News::East, News::South ].contains( &harness.player().direction() ) );
+    assert_eq!( harness.player_translation(), start.to_3dxz() );
+}
+
+#[test]
+fn forward_ends_on_next_cell_and_transform()
+{   let mut harness = Harness::new( TEST_MAZE );
+    harness.face( News::East );
+    harness.step( KeyCode::Up );
+    harness.step( KeyCode::Up );
+
+    let cell = IVec2::new( 3, 1 );
+    assert_eq!( harness.player().position(), cell );
+    assert_eq!( harness.player_translation(), cell.to_3dxz() );
+    assert_eq!( harness.0.world.resource::<StageRecord>().steps, 2 );
+}
+
+#[test]
+fn backward_keeps_facing()
+{   let mut harness = Harness::new( TEST_MAZE );
+    harness.face( News::North );
+    harness.step( KeyCode::Down );
+
+    let cell = IVec2::new( 1, 2 );
+    assert_eq!( harness.player().position(), cell );
+    assert_eq!( harness.player().direction(), News::North );
+    assert_eq!( harness.player_translation(), cell.to_3dxz() );
+}
+
+#[test]
+fn walls_block_movement()
+{   let mut harness = Harness::new( TEST_MAZE );
+    let start = IVec2::new( 1, 1 );
+
+    //北も西も壁
+    for news in [ News::North, News::West ]
+    {   harness.face( news );
+        harness.press( KeyCode::Up );
+
+        assert!( harness.player().is_stop(), "blocked move must not start an animation" );
+        assert_eq!( harness.player().position(), start );
+        assert_eq!( harness.player_translation(), start.to_3dxz() );
+    }
+    assert_eq!( harness.0.world.resource::<StageRecord>().steps, 0 );
+}
+
+#[test]
+fn turning_rotates_figure_back_to_exact_direction()
+{   let mut harness = Harness::new( TEST_MAZE );
+    harness.face( News::North );
+    harness.step( KeyCode::Left );
+
+    assert_eq!( harness.player().direction(), News::West );
+    let rotation = harness.0.world
+        .query_filtered::<&Transform, With<player::FigureHead>>()
+        .single( &harness.0.world ).rotation;
+    assert!( rotation.dot( News::West.to_quat() ).abs() > 1.0 - 1e-6 ); //qと-qは同じ向き
+}
+
+#[test]
+fn space_switches_fpp_and_tpp_cameras()
+{   let mut harness = Harness::new( TEST_MAZE );
+    assert!(   harness.camera_is_active::<FppCamera>() );
+    assert!( ! harness.camera_is_active::<TppCamera>() );
+
+    harness.press( KeyCode::Space );
+    assert!( ! harness.camera_is_active::<FppCamera>() );
+    assert!(   harness.camera_is_active::<TppCamera>() );
+    assert!( harness.0.world.resource::<OrbitCamera>().is_active );
+
+    //三人称視点ではPlayerを操作できない
+    let before = harness.player().direction();
+    harness.press( KeyCode::Right );
+    assert_eq!( harness.player().direction(), before );
+
+    harness.press( KeyCode::Space );
+    assert!(   harness.camera_is_active::<FppCamera>() );
+    assert!( ! harness.camera_is_active::<TppCamera>() );
+}
+
+#[test]
+fn change_state_moves_to_target_state()
+{   let mut app = App::new();
+    app
+    .add_plugins( MinimalPlugins )
+    .add_state::<MyState>()
+    .add_systems( OnEnter ( MyState::GameStart ), misc::change_state::<MainLoop> );
+
+    app.world.resource_mut::<NextState<MyState>>().set( MyState::GameStart );
+    app.update(); //GameStartへ遷移し、OnEnterでMainLoopを予約する
+    app.update(); //MainLoopへ遷移する
+
+    assert_eq!( *app.world.resource::<State<MyState>>().get(), MyState::MainLoop );
+}
+
+#[test]
+fn stairs_climb_to_the_upper_floor_and_back()
+{   let mut harness = Harness::new( TEST_MAZE_FLOORS );
+    let floor = | harness: &Harness | harness.0.world.resource::<map::Map>().floor();
+
+    //階段以外では上り下りしない
+    harness.step( KeyCode::PageUp );
+    assert_eq!( floor( &harness ), 0 );
+
+    harness.face( News::East );
+    for _ in 0..3 { harness.step( KeyCode::Up ) }
+    let stairs = IVec2::new( 4, 1 );
+    assert_eq!( harness.player().position(), stairs );
+
+    //上ると今の階が変わり、その階の高さに着く。上った先の視界も記録される
+    harness.step( KeyCode::PageUp );
+    assert_eq!( floor( &harness ), 1 );
+    assert_eq!( harness.player().position(), stairs );
+    assert_eq!( harness.player_translation(), stairs.to_3dxz_floor( 1 ) );
+    assert!( harness.0.world.resource::<map::Map>().has_flag( stairs, map::Flag::Visited ) );
+    assert_eq!( harness.0.world.resource::<StageRecord>().steps, 4 );
+
+    //上の階に上り階段はない。下りると元の階に戻る
+    harness.step( KeyCode::PageUp );
+    assert_eq!( floor( &harness ), 1 );
+    harness.step( KeyCode::PageDown );
+    assert_eq!( floor( &harness ), 0 );
+    assert_eq!( harness.player_translation(), stairs.to_3dxz() );
+}
+
+#[test]
+fn keys_pressed_together_start_only_one_action()
+{   let mut harness = Harness::new( TEST_MAZE_FLOORS );
+    let floor = | harness: &Harness | harness.0.world.resource::<map::Map>().floor();
+    harness.face( News::East );
+
+    //前進と旋回を同時に押すと、どちらか一方だけ動く
+    harness.press_together( &[ KeyCode::Up, KeyCode::Right ] );
+    harness.run_until_stop();
+    let ( position, direction ) = ( harness.player().position(), harness.player().direction() );
+    assert!
+    (   ( position, direction ) == ( IVec2::new( 2, 1 ), News::East ) ||
+        ( position, direction ) == ( IVec2::new( 1, 1 ), News::South ),
+        "{position} {direction:?}"
+    );
+    assert_eq!( harness.player_translation(), position.to_3dxz() );
+    harness.face( News::East );
+    while harness.player().position().x < 3 { harness.step( KeyCode::Up ) }
+
+    //階段の手前で前進と上りを同時に押すと、階段へ進むだけで上らない
+    harness.press_together( &[ KeyCode::Up, KeyCode::PageUp ] );
+    harness.run_until_stop();
+    let stairs = IVec2::new( 4, 1 );
+    assert_eq!( floor( &harness ), 0 );
+    assert_eq!( harness.player().position(), stairs );
+    assert_eq!( harness.player_translation(), stairs.to_3dxz() );
+    assert_eq!( harness.0.world.resource::<StageRecord>().steps, 3 );
+}
+
+#[test]
+fn watch_mode_replays_the_build_and_returns_to_fpp()
+{   //迷路を作る手順を記録させて作る
+    let mut map = map::Map::new( IVec2::new( 11, 11 ), default() );
+    map.build_log = Some ( Vec::new() );
+    map.reseed( 1 );
+    map.generate();
+
+    let mut app = App::new();
+    app
+    .add_plugins( MinimalPlugins )
+    .add_plugins( AssetPlugin::default() )
+    .add_asset::<Mesh>()
+    .add_asset::<StandardMaterial>()
+    .insert_resource( TimeUpdateStrategy::ManualDuration( FRAME_TIME ) )
+    .init_resource::<OrbitCamera>()
+    .init_resource::<StageRecord>()
+    .init_resource::<watch::Watch>()
+    .insert_resource( map )
+    .add_systems( Startup, ( player::spawn_entity, watch::spawn_entity ) )
+    .add_systems
+    (   Update,
+        (   watch::play.run_if( watch::is_watching ),
+            watch::update_view,
+        )
+        .chain()
+    );
+    app.world.resource_mut::<watch::Watch>().speed = 1000.0;
+    let count = | app: &mut App | app.world.query::<&watch::WatchEntity>().iter( &app.world ).count();
+    let is_active = | app: &mut App, is_tpp: bool |
+        app.world.query::<( &Camera, Option<&TppCamera> )>().iter( &app.world )
+            .find( | ( _, o_tpp ) | o_tpp.is_some() == is_tpp )
+            .is_some_and( | ( camera, _ ) | camera.is_active );
+
+    //再生中は三人称視点で見下ろす。記録した手順は受け取られる
+    app.update();
+    assert_eq!( count( &mut app ), 1 );
+    assert!(   is_active( &mut app, true  ) );
+    assert!( ! is_active( &mut app, false ) );
+    assert!( app.world.resource::<map::Map>().build_log.as_ref().is_some_and( | log | log.is_empty() ) );
+
+    //再生し終えると、再生用の3Dオブジェクトが消えて一人称視点に戻る
+    for _ in 0..FRAMES_MAX
+    {   app.update();
+        if count( &mut app ) == 0 { break }
+    }
+    assert_eq!( count( &mut app ), 0 );
+    assert!( ! is_active( &mut app, true  ) );
+    assert!(   is_active( &mut app, false ) );
+    assert!( ! app.world.resource::<OrbitCamera>().is_active );
+}
+
+#[test]
+fn stage_score_saturates_instead_of_overflowing()
+{   //最短経路どおりなら歩数×点数×レベル、遠回りすると減る
+    assert_eq!( progress::stage_score( 20, 20, 3 ), 20 * 10 * 3 );
+    assert_eq!( progress::stage_score( 20, 40, 3 ), 20 * 10 * 20 / 40 * 3 );
+    assert_eq!( progress::stage_score( 0, 0, 1 ), 0 );
+
+    //最大の迷路の最短経路でも、高いレベルでも桁あふれしない
+    let par = ( MAP_GRIDS_MAX * MAP_GRIDS_MAX / 2 ) as usize;
+    assert_eq!( progress::stage_score( par, par as u32, 1 ), par as u32 * 10 );
+    assert_eq!( progress::stage_score( par, par as u32, u32::MAX ), u32::MAX );
+}
+
+#[test]
+fn endless_levels_lose_loops_and_reach_the_difficulty_target()
+{   let mut last_braid = f32::MAX;
+    for level in 1..=10
+    {   let mut map = map::Map::new( IVec2::splat( MAP_GRIDS_MIN ), default() );
+        progress::set_level( &mut map, level );
+        assert!( map.braid <= last_braid, "level {level}" );
+        last_braid = map.braid;
+
+        map.reseed( level as u64 );
+        map.generate();
+        let target = progress::difficulty_target( level );
+        progress::regenerate_until( &mut map, target );
+        assert!( map.difficulty() >= target, "level {level}: {} < {target}", map.difficulty() );
+    }
+    assert_eq!( last_braid, 0.0 );
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
@@ -0,0 +1,285 @@
+use super::*;
+
+//standard library
+use std::collections::{ HashMap, HashSet };
+
+//壁のメッシュを作る
+use map::{ wall_mesh, WallMesh, WALL_CHUNK_GRIDS, WALL_CUBE_SIZE };
+
+////////////////////////////////////////////////////////////////////////////////
+
+//見物モード：迷路を作る手順を3Dで再生する（三人称視点カメラで全体を見下ろす）
+//コマンドライン引数か設定ファイルで、一秒あたりの手順数を指定すると有効になる（例：--watch 200）
+//再生中は[+][-]キーで速さを変え、[Enter]キーで最後まで飛ばす
+
+//見物モードの設定
+const WATCH_SPEED_MIN: f32 = 1.0;             //一秒あたりの手順数の下限
+const WATCH_SPEED_MAX: f32 = 100000.0;        //一秒あたりの手順数の上限
+const WATCH_CAMERA_R_PER_GRID: f32 = 1.2;     //カメラの距離(マップの縦横幅の大きい方に掛ける)
+const WATCH_CAMERA_THETA: f32 = PI * 0.8;     //カメラの垂直角度(ラジアン)
+const WATCH_WALL_COLOR: Color = Color::BISQUE;     //作りかけの壁の色
+const WATCH_HEAD_COLOR: Color = Color::LIME_GREEN; //掘っている位置の目印の色
+const WATCH_HEAD_RADIUS: f32 = 0.3;                //掘っている位置の目印の半径
+
+//再生用の3Dオブジェクトの親になるEntityのComponent
+#[derive( Component )]
+pub struct WatchEntity;
+
+//掘っている位置の目印のComponent
+#[derive( Component )]
+pub struct WatchHead;
+
+//見物モードの状態のResource
+#[derive( Resource )]
+pub struct Watch
+{   pub speed: f32,                       //一秒あたりに再生する手順の数（0.0なら見物しない）
+    steps : Vec<( usize, map::BuildStep )>, //再生する手順（階と一歩）
+    played: usize,                        //再生済みの手順の数
+    budget: f32,                          //このフレームで再生できる手順の数（端数は持ち越す）
+    floor : usize,                        //再生中の階
+    center: Vec3,                         //一階の中央（カメラの注視点）
+    floors: Vec<Entity>,                  //階ごとの親Entity
+    size  : IVec2,                        //マップの縦横幅
+    walls : Vec<Vec<bool>>,               //階ごとの再生中の壁（x×縦幅＋yの位置）
+    chunks: HashMap<( usize, IVec2 ), Entity>, //階とチャンクごとの壁のメッシュのEntity
+    dirty : HashSet<( usize, IVec2 )>,    //メッシュを作り直す階とチャンク
+    wall_material: Handle<StandardMaterial>,
+}
+
+impl Default for Watch
+{   fn default() -> Self
+    {   Self
+        {   speed : watch_setting(),
+            steps : Vec::new(),
+            played: 0,
+            budget: 0.0,
+            floor : 0,
+            center: Vec3::ZERO,
+            floors: Vec::new(),
+            size  : IVec2::ZERO,
+            walls : Vec::new(),
+            chunks: HashMap::new(),
+            dirty : HashSet::new(),
+            wall_material: Handle::default(),
+        }
+    }
+}
+
+impl Watch
+{   //再生中のマスが壁か（マップの外はfalse）
+    fn is_wall( &self, floor: usize, cell: IVec2 ) -> bool
+    {   let is_inside = cell.cmpge( IVec2::ZERO ).all() && cell.cmplt( self.size ).all();
+        is_inside && self.walls[ floor ][ ( cell.x * self.size.y + cell.y ) as usize ]
+    }
+
+    //マスを壁か空地にして、そのマスと隣のマスのチャンクにメッシュを作り直す印を付ける
+    fn set_wall( &mut self, floor: usize, cell: IVec2, is_wall: bool )
+    {   if cell.cmplt( IVec2::ZERO ).any() || cell.cmpge( self.size ).any() { return }
+        self.walls[ floor ][ ( cell.x * self.size.y + cell.y ) as usize ] = is_wall;
+        for next in NEWS.iter().map( | &news | cell + news ).chain( [ cell ] )
+        {   self.dirty.insert( ( floor, next.clamp( IVec2::ZERO, self.size - 1 ) / WALL_CHUNK_GRIDS ) );
+        }
+    }
+
+    //印を付けたチャンクの壁のメッシュを作り直す（壁がなくなったチャンクはEntityを消す）
+    fn rebuild_chunks( &mut self, cmds: &mut Commands, meshes: &mut Assets<Mesh> )
+    {   for ( floor, chunk ) in std::mem::take( &mut self.dirty )
+        {   if let Some ( id ) = self.chunks.remove( &( floor, chunk ) ) { cmds.entity( id ).despawn_recursive() }
+
+            let zero = chunk * WALL_CHUNK_GRIDS;
+            let end = ( zero + WALL_CHUNK_GRIDS ).min( self.size );
+            let is_wall = | cell | self.is_wall( floor, cell );
+            let Some ( mesh ) = wall_mesh( is_wall, None, zero.x..end.x, zero.y..end.y, WALL_CUBE_SIZE ) else { continue };
+            let id = cmds.spawn( PbrBundle::default() )
+            .insert( meshes.add( mesh ) )
+            .insert( self.wall_material.clone() )
+            .set_parent( self.floors[ floor ] )
+            .id();
+            self.chunks.insert( ( floor, chunk ), id );
+        }
+    }
+}
+
+//一秒あたりの手順数を得る（指定がなければ0.0で、見物しない）
+fn watch_setting() -> f32
+{   let Some ( speed ) = misc::setting( "watch" ) else { return 0.0 };
+    match speed.parse::<f32>()
+    {   Ok ( speed ) if speed > 0.0 => speed.clamp( WATCH_SPEED_MIN, WATCH_SPEED_MAX ),
+        _ => { warn!( "Invalid watch speed \"{speed}\"" ); 0.0 },
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//再生中か（run_if用）
+pub fn is_watching( watch: Res<Watch> ) -> bool { watch.played < watch.steps.len() }
+
+//見物モードなら、迷路を作る手順を記録させる（make_new_dataより前に実行する）
+pub fn start_recording
+(   mut map: ResMut<map::Map>,
+    watch: Res<Watch>,
+)
+{   map.build_log = ( watch.speed > 0.0 ).then( Vec::new );
+}
+
+//記録した手順を受け取り、全部壁の状態の迷路をspawnする（手順がなければ何もしない）
+pub fn spawn_entity
+(   q_watch: Query<Entity, With<WatchEntity>>,
+    mut watch: ResMut<Watch>,
+    mut map: ResMut<map::Map>,
+    mut orbit_camera: ResMut<OrbitCamera>,
+    mut cmds: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+)
+{   //既存の再生があれば削除する
+    q_watch.for_each( | id | cmds.entity( id ).despawn_recursive() );
+    let steps = map.build_log.as_mut().map( std::mem::take ).unwrap_or_default();
+    *watch = Watch { speed: watch.speed, steps, ..default() };
+    if watch.steps.is_empty() { return }
+    info!( "Watching {} build steps", watch.steps.len() );
+
+    watch.wall_material = materials.add( WATCH_WALL_COLOR.into() );
+    let head = StandardMaterial { base_color: WATCH_HEAD_COLOR, emissive: WATCH_HEAD_COLOR, ..default() };
+
+    //階ごとの親をspawnする（一階以外は隠しておく）
+    let mut floors = Vec::new();
+    cmds.spawn( ( SpatialBundle::default(), WatchEntity ) )
+    .with_children
+    (   | cmds |
+        {   for floor in 0..map.floor_count()
+            {   let visibility = if floor == 0 { Visibility::Inherited } else { Visibility::Hidden };
+                let transform = Transform::from_translation( IVec2::ZERO.to_3dxz_floor( floor ) );
+                floors.push( cmds.spawn( SpatialBundle { visibility, transform, ..default() } ).id() );
+            }
+
+            //掘っている位置の目印
+            cmds.spawn( ( PbrBundle::default(), WatchHead ) )
+            .insert( meshes.add( shape::UVSphere { radius: WATCH_HEAD_RADIUS, ..default() }.into() ) )
+            .insert( materials.add( head ) )
+            .insert( Transform::from_translation( map.start.to_3dxz() ) )
+            ;
+        }
+    );
+    watch.floors = floors;
+
+    //全部壁の状態から始める（壁はチャンク単位で一つのメッシュにまとめ、変わったチャンクだけ作り直す）
+    let size = IVec2::new( map.width(), map.height() );
+    watch.size  = size;
+    watch.walls = vec![ vec![ true; ( size.x * size.y ) as usize ]; map.floor_count() ];
+    for floor in 0..map.floor_count()
+    {   for ( xs, ys ) in map.wall_chunks()
+        {   watch.dirty.insert( ( floor, IVec2::new( xs.start, ys.start ) / WALL_CHUNK_GRIDS ) );
+        }
+    }
+    watch.rebuild_chunks( &mut cmds, &mut meshes );
+
+    //三人称視点カメラを、迷路全体が見える距離まで引く（注視点はupdate_viewで合わせる）
+    let size = map.width().max( map.height() ) as f32;
+    orbit_camera.orbit.r     = size * WATCH_CAMERA_R_PER_GRID;
+    orbit_camera.orbit.theta = WATCH_CAMERA_THETA;
+    orbit_camera.is_active   = true;
+    watch.center = Vec3::new( map.width() as f32 - 1.0, 0.0, map.height() as f32 - 1.0 ) / 2.0;
+    map.set_floor( 0 );
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//再生中のキー入力
+//[+][-]速さを倍・半分にする、[Enter]最後まで飛ばす
+pub fn catch_input
+(   mut watch: ResMut<Watch>,
+    inkey: Res<Input<KeyCode>>,
+)
+{   for keycode in inkey.get_just_pressed()
+    {   match keycode
+        {   KeyCode::Equals | KeyCode::Plus | KeyCode::NumpadAdd =>
+                watch.speed = ( watch.speed * 2.0 ).min( WATCH_SPEED_MAX ),
+            KeyCode::Minus | KeyCode::NumpadSubtract =>
+                watch.speed = ( watch.speed / 2.0 ).max( WATCH_SPEED_MIN ),
+            KeyCode::Return =>
+                watch.played = watch.steps.len(), //update_viewで後片付けされる
+            _ => (),
+        }
+    }
+}
+
+//経過時間に応じて手順を再生する（壁を消す・置く、目印を動かす。変わったチャンクのメッシュは作り直す）
+pub fn play
+(   mut q_head: Query<&mut Transform, With<WatchHead>>,
+    mut watch: ResMut<Watch>,
+    mut cmds: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    time: Res<Time>,
+)
+{   let Ok ( mut head ) = q_head.get_single_mut() else { return };
+
+    watch.budget += watch.speed * time.delta().as_secs_f32();
+    while watch.budget >= 1.0 && watch.played < watch.steps.len()
+    {   let ( floor, step ) = watch.steps[ watch.played ];
+        watch.played += 1;
+        watch.budget -= 1.0;
+
+        //階が変わったら、その階まで表示する
+        if floor != watch.floor
+        {   watch.floor = floor;
+            for ( i, &id ) in watch.floors.iter().enumerate()
+            {   let visibility = if i <= floor { Visibility::Inherited } else { Visibility::Hidden };
+                cmds.entity( id ).insert( visibility );
+            }
+        }
+
+        let cell = match step
+        {   map::BuildStep::Dig  ( cell ) => { watch.set_wall( floor, cell, false ); cell }
+            map::BuildStep::Fill ( cell ) => { watch.set_wall( floor, cell, true  ); cell }
+            map::BuildStep::Backtrack ( cell ) => cell,
+        };
+        head.translation = cell.to_3dxz_floor( floor );
+    }
+    watch.rebuild_chunks( &mut cmds, &mut meshes );
+
+    //再生し終えたら、端数は捨てる
+    if watch.played >= watch.steps.len() { watch.budget = 0.0 }
+}
+
+//再生中は三人称視点カメラで迷路全体を見下ろし、完成した迷路を隠す
+//再生し終えたら、再生用の3Dオブジェクトを削除して完成した迷路を表示し、一人称視点に戻す
+pub fn update_view
+(   q_watch: Query<Entity, With<WatchEntity>>,
+    mut q_map: Query<&mut Visibility, With<map::MapZeroEntity>>,
+    mut q_cameras: Query<( &mut Camera, Option<&TppCamera> ), With<Camera3d>>,
+    q_player: Query<&Transform, With<player::Player>>,
+    watch: Res<Watch>,
+    mut orbit_camera: ResMut<OrbitCamera>,
+    mut cmds: Commands,
+)
+{   let is_watching = watch.played < watch.steps.len();
+    if ! is_watching && q_watch.is_empty() { return }
+
+    //三人称視点カメラ(再生中)か一人称視点カメラ(再生後)をアクティブにする
+    q_cameras.for_each_mut
+    (   | ( mut camera, o_tpp ) |
+        {   let is_active = o_tpp.is_some() == is_watching;
+            if camera.is_active != is_active { camera.is_active = is_active }
+        }
+    );
+
+    //完成した迷路は再生し終えるまで隠す
+    let visibility = if is_watching { Visibility::Hidden } else { Visibility::Inherited };
+    q_map.for_each_mut( | mut map_visibility | if *map_visibility != visibility { *map_visibility = visibility } );
+
+    if is_watching
+    {   //注視点は再生中の階の中央（カメラはPlayerの子なので、Playerからの相対位置にする）
+        let Ok ( player ) = q_player.get_single() else { return };
+        orbit_camera.look_at = watch.center + IVec2::ZERO.to_3dxz_floor( watch.floor ) - player.translation;
+        orbit_camera.is_active = true;
+    }
+    else
+    {   q_watch.for_each( | id | cmds.entity( id ).despawn_recursive() );
+        *orbit_camera = OrbitCamera::default();
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
\ No newline at end of file
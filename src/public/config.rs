@@ -21,11 +21,9 @@ pub static MAIN_WINDOW: Lazy<Option<Window>> = Lazy::new
 ////////////////////////////////////////////////////////////////////////////////
 
 //アプリの情報
-const _CARGO_TOML_NAME: &str = env!( "CARGO_PKG_NAME"    );
 const _CARGO_TOML_VER : &str = env!( "CARGO_PKG_VERSION" );
 
-const APP_TITLE: &str = _CARGO_TOML_NAME; //アプリタイトル
-const APP_VER  : &str = _CARGO_TOML_VER;  //アプリのバージョン
+pub const APP_VER  : &str = _CARGO_TOML_VER;  //アプリのバージョン
 
 ////////////////////////////////////////////////////////////////////////////////
 
@@ -92,6 +90,9 @@ pub const ASSETS_SPRITE_KANI_DOTOWN: &str = "sprites/kani_DOTOWN.png";
 pub const ASSETS_FONT_ORBITRON_BLACK      : &str = "fonts/Orbitron-Black.ttf";
 pub const ASSETS_FONT_PRESSSTART2P_REGULAR: &str = "fonts/PressStart2P-Regular.ttf";
 
+//assets（迷路ファイルの拡張子）
+pub const ASSETS_LEVEL_EXTENSION: &str = "maze";
+
 //事前ロード対象
 counted_array!
 (   pub const PRELOAD_ASSETS: [ &str; _ ] =
@@ -109,6 +110,8 @@ counted_array!
 pub const DEPTH_SPRITE_DEBUG_GRID : f32 = 999.0; //重なりの最大値
 pub const DEPTH_SPRITE_KANI_DOTOWN: f32 = 900.0;
 pub const DEPTH_SPRITE_GAME_FRAME : f32 = 800.0;
+pub const DEPTH_SPRITE_AUTOMAP    : f32 = 750.0;
+pub const DEPTH_SPRITE_MINIMAP    : f32 = 700.0;
 
 ////////////////////////////////////////////////////////////////////////////////
 
@@ -136,16 +139,16 @@ pub static SCREEN_FRAME: Lazy<ScreenFrame> = Lazy::new
     {   let design = vec!
         [  //0123456789 123456789 123456789 123456789 12
             "###########################################", //0
-            "#..............................############", //1
-            "#..............................############", //2
-            "#..............................############", //3
-            "#..............................############", //4
-            "#..............................############", //5
-            "#..............................############", //6
-            "#..............................############", //7
-            "#..............................############", //8
-            "#..............................############", //9
-            "#..............................############", //10
+            "#..............................#..........#", //1
+            "#..............................#..........#", //2
+            "#..............................#..........#", //3
+            "#..............................#..........#", //4
+            "#..............................#..........#", //5
+            "#..............................#..........#", //6
+            "#..............................#..........#", //7
+            "#..............................#..........#", //8
+            "#..............................#..........#", //9
+            "#..............................#..........#", //10
             "#..............................############", //11
             "#..............................############", //12
             "#..............................############", //13
@@ -170,24 +173,14 @@ pub static SCREEN_FRAME: Lazy<ScreenFrame> = Lazy::new
     }
 );
 
-////////////////////////////////////////////////////////////////////////////////
-
-//マップ縦横幅(Grid)
-pub const MAP_GRIDS_WIDTH : i32 = 51;
-pub const MAP_GRIDS_HEIGHT: i32 = 51;
-
-//マップのレンジ（外壁含む）
-pub const MAP_GRIDS_X_RANGE: Range<i32> = 0..MAP_GRIDS_WIDTH;
-pub const MAP_GRIDS_Y_RANGE: Range<i32> = 0..MAP_GRIDS_HEIGHT;
-
-//外壁を含まないレンジ
-pub const MAP_GRIDS_X_RANGE_INNER: Range<i32> = 1..MAP_GRIDS_WIDTH  - 1;
-pub const MAP_GRIDS_Y_RANGE_INNER: Range<i32> = 1..MAP_GRIDS_HEIGHT - 1;
+//ミニマップの表示エリア(Grid)：画面デザインの右上の空き
+pub const MINIMAP_GRIDS_ZERO: IVec2 = IVec2::new( 32,  1 ); //左上
+pub const MINIMAP_GRIDS_SIZE: IVec2 = IVec2::new( 10, 10 ); //縦横幅
 
 ////////////////////////////////////////////////////////////////////////////////
 
-//四方の配列
-pub const NEWS: [ News; 4 ] = [ News::South, News::East, News::West, News::North ];
+//迷路の一階分の高さ（マップ縦横幅と階数の上限はライブラリのshared/config.rs）
+pub const MAP_FLOOR_HEIGHT: f32 = 1.0;
 
 ////////////////////////////////////////////////////////////////////////////////
 
@@ -125,6 +125,11 @@ pub fn despawn<T: Component>
 
 ////////////////////////////////////////////////////////////////////////////////
 
+//設定値を得る（ライブラリと同じ読み方をする）
+pub use chip14::shared::misc::setting;
+
+////////////////////////////////////////////////////////////////////////////////
+
 //Stateの無条件遷移
 pub fn change_state<T: Send + Sync + Default + GotoState>
 (   state: Local<T>,
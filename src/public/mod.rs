@@ -1,5 +1,8 @@
 use super::*;
 
+//ライブラリと共有する定数・型・設定の読み方
+pub use chip14::shared::*;
+
 //アプリの設定
 mod config;
 pub use config::*;
@@ -6,6 +6,7 @@ use super::*;
 pub trait GridToPixel
 {   fn to_screen_pixel( &self ) -> Vec2;
     fn to_3dxz( &self ) -> Vec3;
+    fn to_3dxz_floor( &self, floor: usize ) -> Vec3;
 }
 
 //glamの型にメソッドを追加する
@@ -30,6 +31,10 @@ impl GridToPixel for IVec2
         Vec3::new( x, y, z )
     }
 
+    //平面座標(IVec2)から、指定の階の高さの3D直交座標(Vec3)へ変換する
+    fn to_3dxz_floor( &self, floor: usize ) -> Vec3
+    {   self.to_3dxz() + Vec3::Y * MAP_FLOOR_HEIGHT * floor as f32
+    }
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -80,6 +85,39 @@ impl Default for OrbitCamera
 
 ////////////////////////////////////////////////////////////////////////////////
 
+//迷路の乱数シードのResource
+#[derive( Resource, Clone, Copy, PartialEq, Eq, Debug )]
+pub struct MazeSeed ( pub u64 );
+
+impl Default for MazeSeed
+{   fn default() -> Self
+    {   //コマンドライン引数か設定ファイルで指定できる（例：--seed 1234567890）
+        if let Some ( seed ) = misc::setting( "seed" )
+        {   match seed.parse()
+            {   Ok ( seed ) => return Self ( seed ),
+                Err ( _ ) => warn!( "Invalid maze seed \"{seed}\"" ),
+            }
+        }
+
+        let seed_dev = 1234567890;
+        let seed_rel = || rand::thread_rng().gen::<u64>();
+        Self ( if misc::DEBUG() { seed_dev } else { seed_rel() } )
+    }
+}
+
+//進行状況のResource（ステージをクリアするとレベルが上がり、スコアが累計される）
+#[derive( Resource, Clone, Copy, PartialEq, Eq, Debug )]
+pub struct Progress
+{   pub level: u32, //レベル(1～)
+    pub score: u32, //累計スコア
+}
+
+impl Default for Progress
+{   fn default() -> Self { Self { level: 1, score: 0 } }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
 //ゲームの状態
 #[derive( Clone, Copy, Eq, PartialEq, Hash, Debug, Default, States )]
 pub enum MyState
@@ -87,6 +125,7 @@ pub enum MyState
     InitApp,
     GameStart,
     MainLoop,
+    StageClear,
 }
 
 //Stateの遷移に使うマーカー(not Resource)
@@ -115,79 +154,4 @@ pub struct ScreenFrame<'a>
 
 ////////////////////////////////////////////////////////////////////////////////
 
-//四方を表す列挙型
-#[derive( Default, Clone, Copy )]
-pub enum News { #[default] North, East, West, South }
-
-//IVec2 = IVec2 + News
-impl Add<News> for IVec2
-{   type Output = IVec2;
-    fn add( mut self, news: News ) -> IVec2
-    {   match news
-        {   News::North => { self.y -= 1; }
-            News::East  => { self.x += 1; }
-            News::West  => { self.x -= 1; }
-            News::South => { self.y += 1; }
-        }
-        self
-    }
-}
-
-//IVec2 += News
-impl AddAssign<News> for IVec2
-{   fn add_assign( &mut self, news: News )
-    {   match news
-        {   News::North => { self.y -= 1; }
-            News::East  => { self.x += 1; }
-            News::West  => { self.x -= 1; }
-            News::South => { self.y += 1; }
-        }
-    }
-}
-
-impl News
-{   //四方に対応するXZ平面上の角度（四元数）を返す
-    #[allow(clippy::wrong_self_convention)]
-    pub fn to_quat( &self ) -> Quat
-    {   match self
-        {   News::North => Quat::from_rotation_y( PI * 0.0 ),
-            News::East  => Quat::from_rotation_y( PI * 1.5 ),
-            News::West  => Quat::from_rotation_y( PI * 0.5 ),
-            News::South => Quat::from_rotation_y( PI * 1.0 ),
-        }
-    }
-
-    //時計回りで方角を得る
-    pub fn turn_right( &self ) -> Self
-    {   match self
-        {   News::North => News::East,
-            News::East  => News::South,
-            News::West  => News::North,
-            News::South => News::West,
-        }
-    }
-
-    //反時計回りで方角を得る
-    pub fn turn_left( &self ) -> Self
-    {   match self
-        {   News::North => News::West,
-            News::East  => News::North,
-            News::West  => News::South,
-            News::South => News::East,
-        }
-    }
-
-    //背面の方角を得る
-    pub fn back( &self ) -> Self
-    {   match self
-        {   News::North => News::South,
-            News::East  => News::West,
-            News::West  => News::East,
-            News::South => News::North,
-        }
-    }
-}
-
-////////////////////////////////////////////////////////////////////////////////
-
 //End of code.
\ No newline at end of file
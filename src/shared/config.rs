@@ -0,0 +1,40 @@
+use super::*;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//アプリの情報
+const _CARGO_TOML_NAME: &str = env!( "CARGO_PKG_NAME" );
+
+pub const APP_TITLE: &str = _CARGO_TOML_NAME; //アプリタイトル
+
+//設定ファイル（コマンドライン引数で指定がない場合に読む）
+pub const SETTINGS_FILE: &str = "chip14.cfg";
+
+////////////////////////////////////////////////////////////////////////////////
+
+//マップ縦横幅(Grid)の初期値（実際の縦横幅はMapが持ち、生成時に変更できる）
+pub const MAP_GRIDS_WIDTH : i32 = 51;
+pub const MAP_GRIDS_HEIGHT: i32 = 51;
+
+//マップ縦横幅(Grid)の下限と上限（外壁含む）
+pub const MAP_GRIDS_MIN: i32 = 5;
+pub const MAP_GRIDS_MAX: i32 = 1001;
+
+//迷路の階数の上限
+pub const MAP_FLOORS_MAX: usize = 9;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//四方の配列
+pub const NEWS: [ News; 4 ] = [ News::South, News::East, News::West, News::North ];
+
+//周囲8マス
+pub const AROUND: [ IVec2; 8 ] =
+[   IVec2::new( -1, -1 ), IVec2::new( 0, -1 ), IVec2::new( 1, -1 ),
+    IVec2::new( -1,  0 ),                      IVec2::new( 1,  0 ),
+    IVec2::new( -1,  1 ), IVec2::new( 0,  1 ), IVec2::new( 1,  1 ),
+];
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
\ No newline at end of file
@@ -0,0 +1,45 @@
+use super::*;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//設定値を得る
+//コマンドライン引数の「--key value」（または「--key=value」）を優先し、
+//なければ設定ファイルの「key = value」の行を探す
+pub fn setting( key: &str ) -> Option<String>
+{   arg_value( &format!( "--{key}" ) ).or_else( || file_value( SETTINGS_FILE, key ) )
+}
+
+//値のない設定（例：--stats）が有効か
+//コマンドライン引数にあるか、設定ファイルで「key = true」なら有効
+pub fn setting_flag( key: &str ) -> bool
+{   let name = format!( "--{key}" );
+    std::env::args().skip( 1 ).any( | arg | arg == name )
+        || file_value( SETTINGS_FILE, key ).is_some_and( | value | value == "true" )
+}
+
+//コマンドライン引数から値を取り出す
+fn arg_value( name: &str ) -> Option<String>
+{   let mut args = std::env::args().skip( 1 );
+    while let Some ( arg ) = args.next()
+    {   if arg == name { return args.next() }
+        let value = arg.strip_prefix( name ).and_then( | s | s.strip_prefix( '=' ) );
+        if let Some ( value ) = value { return Some ( value.to_string() ) }
+    }
+    None
+}
+
+//設定ファイルから値を取り出す（'#'以降はコメント）
+fn file_value( path: &str, key: &str ) -> Option<String>
+{   let text = std::fs::read_to_string( path ).ok()?;
+    text.lines().find_map
+    (   | line |
+        {   let line = line.split( '#' ).next()?;
+            let ( k, v ) = line.split_once( '=' )?;
+            ( k.trim() == key ).then( || v.trim().to_string() )
+        }
+    )
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
\ No newline at end of file
@@ -0,0 +1,14 @@
+use super::*;
+
+//アプリの設定（迷路の中核で使うもの）
+mod config;
+pub use config::*;
+
+//型定義
+mod types;
+pub use types::*;
+
+//ユーティリティ
+pub mod misc;
+
+//End of code.
\ No newline at end of file
@@ -0,0 +1,80 @@
+use super::*;
+
+////////////////////////////////////////////////////////////////////////////////
+
+//四方を表す列挙型
+#[derive( Default, Clone, Copy, PartialEq, Eq, Debug )]
+pub enum News { #[default] North, East, West, South }
+
+//IVec2 = IVec2 + News
+impl Add<News> for IVec2
+{   type Output = IVec2;
+    fn add( mut self, news: News ) -> IVec2
+    {   match news
+        {   News::North => { self.y -= 1; }
+            News::East  => { self.x += 1; }
+            News::West  => { self.x -= 1; }
+            News::South => { self.y += 1; }
+        }
+        self
+    }
+}
+
+//IVec2 += News
+impl AddAssign<News> for IVec2
+{   fn add_assign( &mut self, news: News )
+    {   match news
+        {   News::North => { self.y -= 1; }
+            News::East  => { self.x += 1; }
+            News::West  => { self.x -= 1; }
+            News::South => { self.y += 1; }
+        }
+    }
+}
+
+impl News
+{   //四方に対応するXZ平面上の角度（四元数）を返す
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_quat( &self ) -> Quat
+    {   match self
+        {   News::North => Quat::from_rotation_y( PI * 0.0 ),
+            News::East  => Quat::from_rotation_y( PI * 1.5 ),
+            News::West  => Quat::from_rotation_y( PI * 0.5 ),
+            News::South => Quat::from_rotation_y( PI * 1.0 ),
+        }
+    }
+
+    //時計回りで方角を得る
+    pub fn turn_right( &self ) -> Self
+    {   match self
+        {   News::North => News::East,
+            News::East  => News::South,
+            News::West  => News::North,
+            News::South => News::West,
+        }
+    }
+
+    //反時計回りで方角を得る
+    pub fn turn_left( &self ) -> Self
+    {   match self
+        {   News::North => News::West,
+            News::East  => News::North,
+            News::West  => News::South,
+            News::South => News::East,
+        }
+    }
+
+    //背面の方角を得る
+    pub fn back( &self ) -> Self
+    {   match self
+        {   News::North => News::South,
+            News::East  => News::West,
+            News::West  => News::East,
+            News::South => News::North,
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+//End of code.
\ No newline at end of file